@@ -0,0 +1 @@
+pub mod page_range_diff;
@@ -0,0 +1,174 @@
+use crate::memory::memory_virtual::{collect_virtual_mappings, VirtualMapping};
+use crate::pt::page_range::{GenericPagePermissions, GenericPageRange};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct DiffSide {
+    pub pa: u64, // Physical address of the beginning of the interval
+    pub range_index: usize,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PageRangeChange {
+    Added {
+        new: DiffSide,
+    },
+    Removed {
+        old: DiffSide,
+    },
+    Changed {
+        old: DiffSide,
+        new: DiffSide,
+        pa_changed: bool,
+        permissions_changed: bool,
+    },
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct PageRangeDiff {
+    pub va: u64,
+    pub extent: u64,
+    pub change: PageRangeChange,
+}
+
+fn get_permissions<RangeType: GenericPagePermissions>(range: &RangeType) -> [bool; 6] {
+    [
+        range.is_user_readable(),
+        range.is_user_writeable(),
+        range.is_user_executable(),
+        range.is_kernel_readable(),
+        range.is_kernel_writeable(),
+        range.is_kernel_executable(),
+    ]
+}
+
+// The next side continues the previous one if the physical addresses are contiguous and
+// the permissions are the same, even if the page ranges differ.
+fn is_continuation<RangeType: GenericPagePermissions>(
+    previous: &DiffSide,
+    previous_extent: u64,
+    next: &DiffSide,
+    ranges: &[RangeType],
+) -> bool {
+    previous.pa + previous_extent == next.pa
+        && get_permissions(&ranges[previous.range_index])
+            == get_permissions(&ranges[next.range_index])
+}
+
+fn is_change_continuation<RangeType: GenericPagePermissions>(
+    previous: &PageRangeDiff,
+    next: &PageRangeChange,
+    old_ranges: &[RangeType],
+    new_ranges: &[RangeType],
+) -> bool {
+    let extent = previous.extent;
+    match (&previous.change, next) {
+        (PageRangeChange::Added { new: a }, PageRangeChange::Added { new: b }) => {
+            is_continuation(a, extent, b, new_ranges)
+        }
+        (PageRangeChange::Removed { old: a }, PageRangeChange::Removed { old: b }) => {
+            is_continuation(a, extent, b, old_ranges)
+        }
+        (
+            PageRangeChange::Changed {
+                old: old_a,
+                new: new_a,
+                pa_changed: pa_changed_a,
+                permissions_changed: permissions_changed_a,
+            },
+            PageRangeChange::Changed {
+                old: old_b,
+                new: new_b,
+                pa_changed: pa_changed_b,
+                permissions_changed: permissions_changed_b,
+            },
+        ) => {
+            pa_changed_a == pa_changed_b
+                && permissions_changed_a == permissions_changed_b
+                && is_continuation(old_a, extent, old_b, old_ranges)
+                && is_continuation(new_a, extent, new_b, new_ranges)
+        }
+        _ => false,
+    }
+}
+
+// Ends are kept as u128 since the last page of the address space ends at 2^64.
+fn get_end(mapping: &VirtualMapping) -> u128 {
+    mapping.va as u128 + mapping.extent as u128
+}
+
+fn find_covering_side(
+    mappings: &[VirtualMapping],
+    index: &mut usize,
+    va: u128,
+) -> Option<DiffSide> {
+    while *index < mappings.len() && get_end(&mappings[*index]) <= va {
+        *index += 1;
+    }
+    mappings
+        .get(*index)
+        .filter(|mapping| mapping.va as u128 <= va)
+        .map(|mapping| DiffSide {
+            pa: mapping.pa + (va as u64 - mapping.va),
+            range_index: mapping.range_index,
+        })
+}
+
+// Compares two snapshots of the same address space. Ranges are split wherever the change differs,
+// and virtual address intervals which did not change are not reported. The reported range
+// indices are the ones at the beginning of each interval.
+pub fn diff_page_ranges<RangeType>(
+    old_ranges: &[RangeType],
+    new_ranges: &[RangeType],
+) -> Vec<PageRangeDiff>
+where
+    RangeType: GenericPageRange + GenericPagePermissions,
+{
+    let old_mappings = collect_virtual_mappings(old_ranges);
+    let new_mappings = collect_virtual_mappings(new_ranges);
+    let mut boundaries: Vec<u128> = old_mappings
+        .iter()
+        .chain(new_mappings.iter())
+        .flat_map(|mapping| [mapping.va as u128, get_end(mapping)])
+        .collect();
+    boundaries.sort();
+    boundaries.dedup();
+
+    let mut diffs: Vec<PageRangeDiff> = vec![];
+    let (mut old_index, mut new_index) = (0, 0);
+    for window in boundaries.windows(2) {
+        let (begin, end) = (window[0], window[1]);
+        let old = find_covering_side(&old_mappings, &mut old_index, begin);
+        let new = find_covering_side(&new_mappings, &mut new_index, begin);
+        let change = match (old, new) {
+            (None, None) => continue,
+            (None, Some(new)) => PageRangeChange::Added { new },
+            (Some(old), None) => PageRangeChange::Removed { old },
+            (Some(old), Some(new)) => {
+                let pa_changed = old.pa != new.pa;
+                let permissions_changed = get_permissions(&old_ranges[old.range_index])
+                    != get_permissions(&new_ranges[new.range_index]);
+                if !pa_changed && !permissions_changed {
+                    continue;
+                }
+                PageRangeChange::Changed {
+                    old,
+                    new,
+                    pa_changed,
+                    permissions_changed,
+                }
+            }
+        };
+        let va = begin as u64;
+        let extent = (end - begin) as u64;
+        if let Some(previous) = diffs.last_mut() {
+            if previous.va as u128 + previous.extent as u128 == begin
+                && is_change_continuation(previous, &change, old_ranges, new_ranges)
+            {
+                previous.extent += extent;
+                continue;
+            }
+        }
+        diffs.push(PageRangeDiff { va, extent, change });
+    }
+    diffs
+}
@@ -1 +1,2 @@
+pub mod alias_analysis;
 pub mod page_range_filter;
@@ -0,0 +1,143 @@
+use crate::pt::page_range::{GenericPageMemoryType, GenericPagePermissions, GenericPageRange};
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct AliasMapping {
+    pub va: u64, // Virtual address of the beginning of the physical interval
+    pub range_index: usize,
+}
+
+// A physical interval which is mapped at more than one virtual address.
+#[derive(Clone, PartialEq, Debug)]
+pub struct PhysicalAlias {
+    pub pa: u64,
+    pub extent: u64,
+    pub mappings: Vec<AliasMapping>,
+    pub writeable_and_executable: bool, // Writeable at one address and executable at another
+    pub user_and_kernel: bool,          // User accessible at one address and kernel only at another
+    pub memory_type_conflict: bool,
+}
+
+#[derive(Clone, Copy)]
+struct PhysicalPiece {
+    pa: u64,
+    extent: u64,
+    va: u64,
+    range_index: usize,
+}
+
+fn is_user_accessible<RangeType: GenericPagePermissions>(range: &RangeType) -> bool {
+    range.is_user_readable() || range.is_user_writeable() || range.is_user_executable()
+}
+
+fn is_writeable<RangeType: GenericPagePermissions>(range: &RangeType) -> bool {
+    range.is_user_writeable() || range.is_kernel_writeable()
+}
+
+fn is_executable<RangeType: GenericPagePermissions>(range: &RangeType) -> bool {
+    range.is_user_executable() || range.is_kernel_executable()
+}
+
+fn create_alias<RangeType>(
+    ranges: &[RangeType],
+    pa: u64,
+    extent: u64,
+    pieces: &[PhysicalPiece],
+) -> PhysicalAlias
+where
+    RangeType: GenericPageRange + GenericPagePermissions + GenericPageMemoryType,
+{
+    let mut writeable_and_executable = false;
+    let mut user_and_kernel = false;
+    for (i, a) in pieces.iter().enumerate() {
+        for b in pieces[i + 1..].iter() {
+            let a_range = &ranges[a.range_index];
+            let b_range = &ranges[b.range_index];
+            writeable_and_executable |= (is_writeable(a_range) && is_executable(b_range))
+                || (is_executable(a_range) && is_writeable(b_range));
+            user_and_kernel |= is_user_accessible(a_range) != is_user_accessible(b_range);
+        }
+    }
+    let mut memory_types: Vec<String> = pieces
+        .iter()
+        .filter_map(|piece| ranges[piece.range_index].get_memory_type())
+        .collect();
+    memory_types.sort();
+    memory_types.dedup();
+    PhysicalAlias {
+        pa,
+        extent,
+        mappings: pieces
+            .iter()
+            .map(|piece| AliasMapping {
+                va: piece.va + (pa - piece.pa),
+                range_index: piece.range_index,
+            })
+            .collect(),
+        writeable_and_executable,
+        user_and_kernel,
+        memory_type_conflict: memory_types.len() > 1,
+    }
+}
+
+// Returns all physical intervals which are mapped more than once, sorted by physical address.
+// An interval ends wherever the set of virtual mappings covering it changes.
+pub fn find_aliases<RangeType>(ranges: &[RangeType]) -> Vec<PhysicalAlias>
+where
+    RangeType: GenericPageRange + GenericPagePermissions + GenericPageMemoryType,
+{
+    let mut pieces = vec![];
+    for (range_index, range) in ranges.iter().enumerate() {
+        let mut va = range.get_va_start();
+        for phys_range in range.get_phys_ranges() {
+            pieces.push(PhysicalPiece {
+                pa: phys_range.phys_base,
+                extent: phys_range.phys_extent,
+                va,
+                range_index,
+            });
+            va += phys_range.phys_extent;
+        }
+    }
+    pieces.sort_by_key(|piece| piece.pa);
+
+    let mut boundaries: Vec<u64> = pieces
+        .iter()
+        .flat_map(|piece| [piece.pa, piece.pa + piece.extent])
+        .collect();
+    boundaries.sort();
+    boundaries.dedup();
+
+    let mut aliases: Vec<PhysicalAlias> = vec![];
+    let mut active: Vec<PhysicalPiece> = vec![];
+    let mut next_piece = 0;
+    for window in boundaries.windows(2) {
+        let (begin, end) = (window[0], window[1]);
+        active.retain(|piece| piece.pa + piece.extent > begin);
+        while next_piece < pieces.len() && pieces[next_piece].pa == begin {
+            active.push(pieces[next_piece]);
+            next_piece += 1;
+        }
+        if active.len() < 2 {
+            continue;
+        }
+        // Extend the previous interval if it is covered by exactly the same mappings.
+        if let Some(previous) = aliases.last_mut() {
+            let same_mappings = previous.pa + previous.extent == begin
+                && previous.mappings.len() == active.len()
+                && previous
+                    .mappings
+                    .iter()
+                    .zip(active.iter())
+                    .all(|(mapping, piece)| {
+                        mapping.range_index == piece.range_index
+                            && mapping.va + (begin - previous.pa) == piece.va + (begin - piece.pa)
+                    });
+            if same_mappings {
+                previous.extent += end - begin;
+                continue;
+            }
+        }
+        aliases.push(create_alias(ranges, begin, end - begin, &active));
+    }
+    aliases
+}
@@ -1,6 +1,8 @@
+use crate::pt::common::Error;
 use crate::pt::page_range::{GenericPage, GenericPageRange};
 use crate::pt::x86::X86PageRange;
 use crate::pt::arm::ArmPageRange;
+use crate::pt::riscv::RiscvPageRange;
 
 pub struct PageRangeFilterX86 {
     writeable: Option<bool>,
@@ -9,6 +11,8 @@ pub struct PageRangeFilterX86 {
     superuser_accessible: Option<bool>,
     has_address: Option<u64>,
     va_range: Option<(Option<u64>, Option<u64>)>,
+    global: Option<bool>,
+    device_memory: Option<bool>,
 }
 
 impl PageRangeFilterX86 {
@@ -20,6 +24,8 @@ impl PageRangeFilterX86 {
             superuser_accessible: None,
             has_address: None,
             va_range: None,
+            global: None,
+            device_memory: None,
         }
     }
 
@@ -47,6 +53,15 @@ impl PageRangeFilterX86 {
         self.va_range = Some((start, end));
     }
 
+    pub fn set_global(&mut self, g: bool) {
+        self.global = Some(g);
+    }
+
+    // Only applies to aarch64 ranges which were parsed with MAIR.
+    pub fn set_device_memory(&mut self, d: bool) {
+        self.device_memory = Some(d);
+    }
+
     pub fn get_writeable(&self) -> Option<bool> {
         self.writeable
     }
@@ -70,51 +85,81 @@ impl PageRangeFilterX86 {
     pub fn get_va_range(&self) -> Option<(Option<u64>, Option<u64>)> {
         self.va_range
     }
+
+    pub fn get_global(&self) -> Option<bool> {
+        self.global
+    }
+
+    pub fn get_device_memory(&self) -> Option<bool> {
+        self.device_memory
+    }
 }
 
-pub fn filter_x86_ranges(
-    ranges: &Vec<X86PageRange>,
-    filter: &PageRangeFilterX86,
-) -> Vec<X86PageRange> {
-    let mut filtered_ranges = vec![];
-    let w_opt = filter.get_writeable();
-    let x_opt = filter.get_executable();
-    let u_opt = filter.get_user_accessible();
-    let s_only_opt = filter.get_only_superuser_accessible();
-    let has_addr_opt = filter.get_has_address();
-    let va_range_opt = filter.get_va_range();
-    let (va_begin, va_end) = if let Some(va_range) = va_range_opt {
+fn get_va_bounds(filter: &PageRangeFilterX86) -> (u64, u64) {
+    if let Some(va_range) = filter.get_va_range() {
         (va_range.0.unwrap_or(0_u64), va_range.1.unwrap_or(u64::MAX))
     } else {
         (0_u64, u64::MAX)
-    };
+    }
+}
 
-    // TODO: find b,e indices using binary search, this can be abstracted.
-    for range in ranges {
-        let mut ok = true;
-        ok &= va_begin < range.get_va();
-        ok &= va_end > range.get_va();
-        if let Some(has_addr) = has_addr_opt {
-            ok &= has_addr >= range.get_va() && has_addr < range.get_va() + range.get_extent();
-        }
-        let attr = range.get_attributes();
-        if let Some(w) = w_opt {
-            ok &= w == attr.writeable;
-        }
-        if let Some(x) = x_opt {
-            ok &= x == !attr.nx;
-        }
-        if let Some(u) = u_opt {
-            ok &= u == attr.user;
-        }
-        if let Some(s_only) = s_only_opt {
-            ok &= s_only == !attr.user;
-        }
-        if ok {
-            filtered_ranges.push(range.clone());
-        }
+fn is_x86_range_selected(range: &X86PageRange, filter: &PageRangeFilterX86) -> bool {
+    let (va_begin, va_end) = get_va_bounds(filter);
+    let mut ok = true;
+    ok &= va_begin < range.get_va();
+    ok &= va_end > range.get_va();
+    if let Some(has_addr) = filter.get_has_address() {
+        ok &= has_addr >= range.get_va() && has_addr < range.get_va() + range.get_extent();
     }
-    filtered_ranges
+    let attr = range.get_attributes();
+    if let Some(w) = filter.get_writeable() {
+        ok &= w == attr.writeable;
+    }
+    if let Some(x) = filter.get_executable() {
+        ok &= x == !attr.nx;
+    }
+    if let Some(u) = filter.get_user_accessible() {
+        ok &= u == attr.user;
+    }
+    if let Some(s_only) = filter.get_only_superuser_accessible() {
+        ok &= s_only == !attr.user;
+    }
+    if let Some(g) = filter.get_global() {
+        ok &= g == attr.global;
+    }
+    ok
+}
+
+pub fn filter_x86_ranges(
+    ranges: &[X86PageRange],
+    filter: &PageRangeFilterX86,
+) -> Vec<X86PageRange> {
+    // TODO: find b,e indices using binary search, this can be abstracted.
+    ranges
+        .iter()
+        .filter(|range| is_x86_range_selected(range, filter))
+        .cloned()
+        .collect()
+}
+
+// Filters the ranges of x86::walk while they are produced. The walk yields the ranges in VA
+// order, thus it is stopped as soon as they are past the end of the VA range.
+pub fn filter_x86_walk<'a, I>(
+    walk: I,
+    filter: &'a PageRangeFilterX86,
+) -> impl Iterator<Item = Result<X86PageRange, Error>> + 'a
+where
+    I: Iterator<Item = Result<X86PageRange, Error>> + 'a,
+{
+    let (_, va_end) = get_va_bounds(filter);
+    walk.take_while(move |range| match range {
+        Ok(range) => range.get_va() < va_end,
+        Err(_) => true,
+    })
+    .filter(move |range| match range {
+        Ok(range) => is_x86_range_selected(range, filter),
+        Err(_) => true,
+    })
 }
 
 pub fn filter_aarch64_ranges(
@@ -174,6 +219,59 @@ pub fn filter_aarch64_ranges(
                 ok &= (s == range.is_kernel_writeable()) || (s == range.is_kernel_readable()) || (s == range.is_kernel_executable());
             }
         }
+        if let Some(g) = filter.get_global() {
+            ok &= g != range.attr.not_global;
+        }
+        if let Some(d) = filter.get_device_memory() {
+            ok &= Some(d) == range.attr.is_device_memory();
+        }
+        if ok {
+            filtered_ranges.push(range.clone());
+        }
+    }
+    filtered_ranges
+}
+
+pub fn filter_riscv_ranges(
+    ranges: &Vec<RiscvPageRange>,
+    filter: &PageRangeFilterX86,
+) -> Vec<RiscvPageRange> {
+    let mut filtered_ranges = vec![];
+    let w_opt = filter.get_writeable();
+    let x_opt = filter.get_executable();
+    let u_opt = filter.get_user_accessible();
+    let s_only_opt = filter.get_only_superuser_accessible();
+    let has_addr_opt = filter.get_has_address();
+    let va_range_opt = filter.get_va_range();
+    let (va_begin, va_end) = if let Some(va_range) = va_range_opt {
+        (va_range.0.unwrap_or(0_u64), va_range.1.unwrap_or(u64::MAX))
+    } else {
+        (0_u64, u64::MAX)
+    };
+
+    for range in ranges {
+        let mut ok = true;
+        ok &= va_begin < range.get_va();
+        ok &= va_end > range.get_va();
+        if let Some(has_addr) = has_addr_opt {
+            ok &= has_addr >= range.get_va() && has_addr < range.get_va() + range.get_va_extent();
+        }
+        let attr = range.get_attributes();
+        if let Some(w) = w_opt {
+            ok &= w == attr.writeable;
+        }
+        if let Some(x) = x_opt {
+            ok &= x == attr.executable;
+        }
+        if let Some(u) = u_opt {
+            ok &= u == attr.user;
+        }
+        if let Some(s_only) = s_only_opt {
+            ok &= s_only != attr.user;
+        }
+        if let Some(g) = filter.get_global() {
+            ok &= g == attr.global;
+        }
         if ok {
             filtered_ranges.push(range.clone());
         }
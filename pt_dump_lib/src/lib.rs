@@ -1,3 +1,4 @@
+pub mod diff;
 pub mod filter;
 pub mod memory;
 pub mod print;
@@ -1,2 +1,3 @@
 pub mod memory;
 pub mod memory_fd;
+pub mod memory_virtual;
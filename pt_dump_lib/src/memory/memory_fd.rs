@@ -1,6 +1,7 @@
 use crate::memory::memory::MemoryView;
 use crate::pt::common::Error;
 
+#[derive(Clone)]
 pub struct MemoryViewFd {
     fd: i32,
     gva_base: usize,
@@ -0,0 +1,101 @@
+use crate::memory::memory::MemoryView;
+use crate::pt::common::Error;
+use crate::pt::page_range::GenericPageRange;
+
+// A linear piece of a page range.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct VirtualMapping {
+    pub va: u64,
+    pub pa: u64,
+    pub extent: u64,
+    pub range_index: usize, // Index of the page range the mapping belongs to
+}
+
+// Splits the page ranges into linear mappings sorted by virtual address.
+pub fn collect_virtual_mappings<RangeType: GenericPageRange>(
+    ranges: &[RangeType],
+) -> Vec<VirtualMapping> {
+    let mut mappings = vec![];
+    for (range_index, range) in ranges.iter().enumerate() {
+        // u128 since the last page of the address space ends at 2^64.
+        let mut va = range.get_va_start() as u128;
+        for phys_range in range.get_phys_ranges() {
+            mappings.push(VirtualMapping {
+                va: va as u64,
+                pa: phys_range.phys_base,
+                extent: phys_range.phys_extent,
+                range_index,
+            });
+            va += phys_range.phys_extent as u128;
+        }
+    }
+    mappings.sort_by_key(|mapping| mapping.va);
+    mappings
+}
+
+// Reads virtual addresses by translating them through the given page ranges.
+pub struct VirtualMemoryView<'a> {
+    memory: &'a mut dyn MemoryView,
+    mappings: Vec<VirtualMapping>,
+}
+
+impl<'a> VirtualMemoryView<'a> {
+    pub fn new<RangeType: GenericPageRange>(
+        memory: &'a mut dyn MemoryView,
+        ranges: &[RangeType],
+    ) -> Self {
+        Self {
+            memory,
+            mappings: collect_virtual_mappings(ranges),
+        }
+    }
+
+    pub fn get_mappings(&self) -> &Vec<VirtualMapping> {
+        &self.mappings
+    }
+
+    pub fn find_mapping(&self, va: u64) -> Option<&VirtualMapping> {
+        // u128 since a mapping can end at 2^64.
+        let index = self
+            .mappings
+            .partition_point(|mapping| mapping.va as u128 + mapping.extent as u128 <= va as u128);
+        self.mappings.get(index).filter(|mapping| mapping.va <= va)
+    }
+}
+
+impl<'a> MemoryView for VirtualMemoryView<'a> {
+    fn read_block(&mut self, offset: usize, block_size: usize) -> Result<Vec<u8>, Error> {
+        let mut block = vec![0u8; block_size];
+        self.read_block_inplace(offset, block_size, &mut block[..])?;
+        Ok(block)
+    }
+
+    fn read_block_inplace(
+        &mut self,
+        offset: usize,
+        block_size: usize,
+        block: &mut [u8],
+    ) -> Result<(), Error> {
+        let va = offset as u64;
+        let size = block_size as u64;
+        let mut chunk_offset = 0u64;
+        while chunk_offset < size {
+            // Reads past the end of the address space aren't mapped.
+            let current_va = va.checked_add(chunk_offset).ok_or(Error::UnmappedMemory)?;
+            let mapping = *self.find_mapping(current_va).ok_or(Error::UnmappedMemory)?;
+            // Reads are split at physical discontinuities.
+            let chunk_size = (mapping.extent - (current_va - mapping.va)).min(size - chunk_offset);
+            let current_pa = mapping
+                .pa
+                .checked_add(current_va - mapping.va)
+                .ok_or(Error::UnmappedMemory)?;
+            self.memory.read_block_inplace(
+                current_pa as usize,
+                chunk_size as usize,
+                &mut block[chunk_offset as usize..(chunk_offset + chunk_size) as usize],
+            )?;
+            chunk_offset += chunk_size;
+        }
+        Ok(())
+    }
+}
@@ -1,6 +1,10 @@
-use crate::pt::arm::ArmPageRange;
-use crate::pt::page_range::{GenericPage, GenericPageRange};
-use crate::pt::x86::X86PageRange;
+use crate::diff::page_range_diff::{DiffSide, PageRangeChange, PageRangeDiff};
+use crate::pt::arm::{ArmPageRange, ArmTranslation, ArmWalkEntry};
+use crate::pt::common::Error;
+use crate::pt::ept::EptPageRange;
+use crate::pt::page_range::{GenericPage, GenericPagePermissions, GenericPageRange};
+use crate::pt::riscv::RiscvPageRange;
+use crate::pt::x86::{X86PageRange, X86Translation, X86WalkEntry};
 use colored::*;
 
 fn select_color(w: bool, x: bool, r: bool) -> Color {
@@ -78,6 +82,14 @@ impl X86Writer {
         self.result.push_str(&range.to_string());
         self.result += "\n";
     }
+
+    // Writes the ranges of x86::walk while they are produced. Tables which can't be read are
+    // skipped, as in x86::collect_pages.
+    pub fn write_walk<I: Iterator<Item = Result<X86PageRange, Error>>>(&mut self, walk: I) {
+        for range in walk.filter_map(Result::ok) {
+            self.write_single_range(&range);
+        }
+    }
 }
 
 impl Printer<X86PageRange> for X86Writer {
@@ -104,10 +116,10 @@ impl Aarch64Writer {
             result: String::new(),
         };
         let mut header = format!(
-            "{:>21}   {:>14}  {:>16} {:>17}\n",
-            "Virtual Address", "Length", "User space", "Kernel space"
+            "{:>21}   {:>14}  {:>16} {:>17}  {}\n",
+            "Virtual Address", "Length", "User space", "Kernel space", "Attributes"
         );
-        header.push_str(String::from("-").repeat(74).as_str());
+        header.push_str(String::from("-").repeat(100).as_str());
         header += "\n";
         tmp.result += &header;
         tmp
@@ -154,12 +166,346 @@ impl ToString for ArmPageRange {
         let kx = kx as u8;
         let uinfo = format!("   R:{ur} W:{uw} X:{ux}   ");
         let kinfo = format!("   R:{kr} W:{kw} X:{kx}   ");
+        let mut attr_info = vec![];
+        if let Some(memory_type) = self.attr.get_memory_type() {
+            attr_info.push(memory_type);
+        }
+        attr_info.push(String::from(self.attr.get_shareability_str()));
+        for (is_set, name) in [
+            (self.attr.accessed, "AF"),
+            (self.attr.not_global, "nG"),
+            (self.attr.dbm, "DBM"),
+            (self.attr.guarded, "GP"),
+            (self.attr.contiguous, "Cont"),
+        ] {
+            if is_set {
+                attr_info.push(String::from(name));
+            }
+        }
         format!(
-            "{}|{}|{}{}",
+            "{}|{}|{}{} {}",
             &addr.on_black().to_string(),
             &uinfo.on_color(ucolor).to_string(),
             &kinfo.on_color(kcolor).to_string(),
+            " ".on_black().to_string(),
+            attr_info.join(" ")
+        )
+    }
+}
+
+pub struct RiscvWriter {
+    pub address_column_length: u8,
+    pub length_column_length: u8,
+    pub permissions_column_length: u8,
+    result: String,
+}
+
+impl ToString for RiscvPageRange {
+    fn to_string(&self) -> String {
+        let attr = self.get_attributes();
+        let color = select_color(attr.writeable, attr.executable, attr.readable);
+        let s = format!(
+            "{va:>#21x} : {len:>#14x} : R:{r} W:{w} X:{x} U:{u}",
+            va = self.get_va(),
+            len = self.get_va_extent(),
+            r = attr.readable as u8,
+            w = attr.writeable as u8,
+            x = attr.executable as u8,
+            u = attr.user as u8
+        );
+        format!(
+            "{}{}",
+            &s.on_color(color).to_string(),
+            " ".on_black().to_string()
+        )
+    }
+}
+
+impl RiscvWriter {
+    pub fn new() -> Self {
+        let mut tmp = Self {
+            address_column_length: 18,
+            length_column_length: 12,
+            permissions_column_length: 18,
+            result: String::new(),
+        };
+        let mut header = format!(
+            "{:<20}   {:<14}   {}\n",
+            "Virtual Address", "Length", "Permissions"
+        );
+        header.push_str(String::from("-").repeat(64).as_str());
+        header += "\n";
+        tmp.result += &header;
+        tmp
+    }
+
+    pub fn get_result(&self) -> &String {
+        &self.result
+    }
+
+    fn write_single_range(&mut self, range: &RiscvPageRange) {
+        self.result.push_str(&range.to_string());
+        self.result += "\n";
+    }
+}
+
+impl Printer<RiscvPageRange> for RiscvWriter {
+    fn write_ranges(&mut self, ranges: &Vec<RiscvPageRange>) {
+        for range in ranges {
+            self.write_single_range(range);
+        }
+    }
+}
+
+pub struct EptWriter {
+    pub address_column_length: u8,
+    pub length_column_length: u8,
+    pub permissions_column_length: u8,
+    result: String,
+}
+
+impl ToString for EptPageRange {
+    fn to_string(&self) -> String {
+        let attr = self.get_attributes();
+        let color = select_color(
+            attr.writeable,
+            attr.executable || attr.user_executable,
+            attr.readable,
+        );
+        let s = format!(
+            "{gpa:>#21x} : {len:>#14x} : R:{r} W:{w} X:{x} UX:{ux} {mt}",
+            gpa = self.get_va(),
+            len = self.get_va_extent(),
+            r = attr.readable as u8,
+            w = attr.writeable as u8,
+            x = attr.executable as u8,
+            ux = attr.user_executable as u8,
+            mt = attr.get_memory_type_str()
+        );
+        format!(
+            "{}{}",
+            &s.on_color(color).to_string(),
             " ".on_black().to_string()
         )
     }
 }
+
+impl EptWriter {
+    pub fn new() -> Self {
+        let mut tmp = Self {
+            address_column_length: 18,
+            length_column_length: 12,
+            permissions_column_length: 18,
+            result: String::new(),
+        };
+        let mut header = format!(
+            "{:<20}   {:<14}   {}\n",
+            "Guest Physical Address", "Length", "Permissions"
+        );
+        header.push_str(String::from("-").repeat(72).as_str());
+        header += "\n";
+        tmp.result += &header;
+        tmp
+    }
+
+    pub fn get_result(&self) -> &String {
+        &self.result
+    }
+
+    fn write_single_range(&mut self, range: &EptPageRange) {
+        self.result.push_str(&range.to_string());
+        self.result += "\n";
+    }
+}
+
+impl Printer<EptPageRange> for EptWriter {
+    fn write_ranges(&mut self, ranges: &Vec<EptPageRange>) {
+        for range in ranges {
+            self.write_single_range(range);
+        }
+    }
+}
+
+impl ToString for X86Translation {
+    fn to_string(&self) -> String {
+        let mut result = format!("Translating {:#x}\n", self.va);
+        for step in &self.steps {
+            let attr = &step.attributes;
+            let entry_info = match step.entry {
+                X86WalkEntry::NotPresent => String::from("not present"),
+                X86WalkEntry::Table { next_table_pa } => format!("table {:#x}", next_table_pa),
+                X86WalkEntry::Leaf { page_pa, page_size } => {
+                    format!("page {:#x} size {:#x}", page_pa, page_size)
+                }
+            };
+            result += &format!(
+                "{level:<5} [{index:>4}] @ {table:#x} : {raw:#018x} : W:{w} X:{x} U:{u} A:{a} D:{d} G:{g} -> {entry}\n",
+                level = format!("{:?}", step.level),
+                index = step.index,
+                table = step.table_pa,
+                raw = step.raw_entry,
+                w = attr.writeable as u8,
+                x = !attr.nx as u8,
+                u = attr.user as u8,
+                a = attr.accessed as u8,
+                d = attr.dirty as u8,
+                g = attr.global as u8,
+                entry = entry_info
+            );
+        }
+        match &self.result {
+            Ok(translated) => {
+                let attr = &translated.attributes;
+                result += &format!(
+                    "Result: {:#x} : W:{} X:{} U:{}\n",
+                    translated.pa, attr.writeable as u8, !attr.nx as u8, attr.user as u8
+                );
+            }
+            Err(fault) => {
+                result += &format!("Fault at {:?}: {:?}\n", fault.level, fault.reason);
+            }
+        }
+        result
+    }
+}
+
+impl ToString for ArmTranslation {
+    fn to_string(&self) -> String {
+        let mut result = format!("Translating {:#x}\n", self.va);
+        for step in &self.steps {
+            let entry_info = match &step.entry {
+                ArmWalkEntry::Invalid => String::from("invalid"),
+                ArmWalkEntry::Table {
+                    next_table_pa,
+                    permission_bits,
+                    xn,
+                    pxn,
+                } => format!(
+                    "table {:#x} APTable:{:#b} XNTable:{} PXNTable:{}",
+                    next_table_pa, permission_bits, *xn as u8, *pxn as u8
+                ),
+                ArmWalkEntry::Leaf {
+                    output_pa,
+                    block_size,
+                    attr,
+                } => format!(
+                    "block {:#x} size {:#x} AP:{:#b} XN:{} PXN:{}",
+                    output_pa, block_size, attr.permission_bits, attr.xn as u8, attr.pxn as u8
+                ),
+            };
+            result += &format!(
+                "L{level} [{index:>4}] @ {table:#x} : {raw:#018x} -> {entry}\n",
+                level = step.level,
+                index = step.index,
+                table = step.table_pa,
+                raw = step.raw_entry,
+                entry = entry_info
+            );
+        }
+        match &self.result {
+            Ok(translated) => {
+                let attr = &translated.attr;
+                result += &format!(
+                    "Result: {:#x} : AP:{:#b} XN:{} PXN:{} {}\n",
+                    translated.pa,
+                    attr.permission_bits,
+                    attr.xn as u8,
+                    attr.pxn as u8,
+                    attr.get_memory_type().unwrap_or_default()
+                );
+            }
+            Err(fault) => {
+                result += &format!("Fault at L{}: {:?}\n", fault.level, fault.reason);
+            }
+        }
+        result
+    }
+}
+
+// Renders a page table diff like a unified diff. Changed intervals are shown as a removed line
+// with the old mapping followed by an added line with the new one.
+pub struct DiffWriter {
+    result: String,
+}
+
+impl Default for DiffWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DiffWriter {
+    pub fn new() -> Self {
+        let mut tmp = Self {
+            result: String::new(),
+        };
+        let mut header = format!(
+            " {:>21}   {:>14}   {:>14}   {}\n",
+            "Virtual Address", "Length", "Physical", "Permissions"
+        );
+        header.push_str(String::from("-").repeat(100).as_str());
+        header += "\n";
+        tmp.result += &header;
+        tmp
+    }
+
+    pub fn get_result(&self) -> &String {
+        &self.result
+    }
+
+    fn write_single_line<RangeType: GenericPagePermissions>(
+        &mut self,
+        marker: &str,
+        diff: &PageRangeDiff,
+        side: &DiffSide,
+        range: &RangeType,
+    ) {
+        let ur = range.is_user_readable();
+        let uw = range.is_user_writeable();
+        let ux = range.is_user_executable();
+        let kr = range.is_kernel_readable();
+        let kw = range.is_kernel_writeable();
+        let kx = range.is_kernel_executable();
+        let color = select_color(uw || kw, ux || kx, ur || kr);
+        let s = format!(
+            "{va:>#21x} : {len:>#14x} : {pa:>#14x} : User R:{ur} W:{uw} X:{ux} Kernel R:{kr} W:{kw} X:{kx}",
+            va = diff.va,
+            len = diff.extent,
+            pa = side.pa,
+            ur = ur as u8,
+            uw = uw as u8,
+            ux = ux as u8,
+            kr = kr as u8,
+            kw = kw as u8,
+            kx = kx as u8
+        );
+        self.result.push_str(&format!(
+            "{}{}{}\n",
+            marker,
+            &s.on_color(color).to_string(),
+            " ".on_black().to_string()
+        ));
+    }
+
+    pub fn write_diffs<RangeType: GenericPagePermissions>(
+        &mut self,
+        diffs: &[PageRangeDiff],
+        old_ranges: &[RangeType],
+        new_ranges: &[RangeType],
+    ) {
+        for diff in diffs {
+            match &diff.change {
+                PageRangeChange::Added { new } => {
+                    self.write_single_line("+", diff, new, &new_ranges[new.range_index]);
+                }
+                PageRangeChange::Removed { old } => {
+                    self.write_single_line("-", diff, old, &old_ranges[old.range_index]);
+                }
+                PageRangeChange::Changed { old, new, .. } => {
+                    self.write_single_line("-", diff, old, &old_ranges[old.range_index]);
+                    self.write_single_line("+", diff, new, &new_ranges[new.range_index]);
+                }
+            }
+        }
+    }
+}
@@ -1,4 +1,7 @@
 pub mod arm;
 pub mod common;
+pub mod ept;
+pub mod nested;
 pub mod page_range;
+pub mod riscv;
 pub mod x86;
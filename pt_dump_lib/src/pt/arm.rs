@@ -1,6 +1,7 @@
 use crate::memory::memory::MemoryView;
-use crate::pt::common::{Error, PhysRange};
+use crate::pt::common::{Error, PhysRange, WalkFaultReason};
 use super::page_range::{GenericPage, GenericPageRange};
+use rayon::prelude::*;
 
 #[derive(Copy, Clone)]
 pub enum Granularity {
@@ -11,8 +12,10 @@ pub enum Granularity {
 
 #[derive(Copy, Clone)]
 pub enum ArmFlavour {
-    Arm32,
+    Arm32,     // Short-descriptor format
+    Arm32Lpae, // Long-descriptor format
     Arm64,
+    Arm64Stage2, // IPA to PA translation
 }
 
 #[derive(Copy, Clone)]
@@ -21,6 +24,10 @@ pub struct ArmContext {
     granularity: Granularity,
     virtual_address_space_size: u8,
     top_bit: u8,
+    dacr: u32, // Only used by the short-descriptor format
+    hierarchical_permissions: bool,
+    mair: Option<u64>,
+    stage2_start_level: u8,
 }
 
 impl ArmContext {
@@ -29,24 +36,146 @@ impl ArmContext {
         granularity: Granularity,
         virtual_address_space_size: u8,
         top_bit: u8,
-    ) -> Self {
-        Self {
+    ) -> Result<Self, Error> {
+        // The sizes TTBCR.N and TxSZ can encode.
+        let valid_sizes = match flavour {
+            ArmFlavour::Arm32 | ArmFlavour::Arm32Lpae => 25..=32,
+            ArmFlavour::Arm64 | ArmFlavour::Arm64Stage2 => 16..=52,
+        };
+        if !valid_sizes.contains(&virtual_address_space_size) {
+            return Err(Error::UnsupportedTranslationMode);
+        }
+        Ok(Self {
             flavour: flavour,
             granularity: granularity,
             virtual_address_space_size: virtual_address_space_size,
             top_bit: top_bit,
-        }
+            dacr: DACR_ALL_CLIENT,
+            hierarchical_permissions: true,
+            mair: None,
+            stage2_start_level: 0,
+        })
+    }
+
+    // The start level uses the architectural numbering, i.e. level 3 is the last level.
+    pub fn new_stage2(
+        granularity: Granularity,
+        ipa_size: u8,
+        start_level: u8,
+    ) -> Result<Self, Error> {
+        let mut context = Self::new(ArmFlavour::Arm64Stage2, granularity, ipa_size, 0)?;
+        context.stage2_start_level = start_level;
+        Ok(context)
+    }
+
+    // Decodes T0SZ, SL0 and TG0 of VTCR_EL2.
+    pub fn from_vtcr(vtcr: u64) -> Result<Self, Error> {
+        let ipa_size = 64 - (vtcr & 0x3f) as u8;
+        let sl0 = ((vtcr >> 6) & 0x3) as u8;
+        let (granularity, start_level) = match (vtcr >> 14) & 0x3 {
+            0b00 => (Granularity::Pt4k, if sl0 == 0b11 { 3 } else { 2 - sl0 }),
+            0b01 if sl0 != 0b11 => (Granularity::Pt64k, 3 - sl0),
+            0b10 => (Granularity::Pt16k, 3 - sl0),
+            _ => return Err(Error::UnsupportedTranslationMode),
+        };
+        Self::new_stage2(granularity, ipa_size, start_level)
+    }
+
+    // MAIR_EL1 (or MAIR0/MAIR1 for LPAE) used to resolve AttrIndx into a memory type.
+    pub fn set_mair(&mut self, mair: u64) {
+        self.mair = Some(mair);
+    }
+
+    pub fn set_dacr(&mut self, dacr: u32) {
+        self.dacr = dacr;
+    }
+
+    // If disabled, APTable, UXNTable and PXNTable are ignored and the leaf attributes are reported as is.
+    pub fn set_hierarchical_permissions(&mut self, enabled: bool) {
+        self.hierarchical_permissions = enabled;
     }
 }
 
-#[derive(Clone, PartialEq)]
+// All 16 domains are clients, so accesses are checked against the AP bits.
+const DACR_ALL_CLIENT: u32 = 0x55555555;
+
+// The AArch64 AP[2:1] encodings are used as is. AArch32 additionally has the
+// "PL1 read/write, PL0 read-only" encoding which has no AArch64 counterpart.
+pub const PERMISSION_BITS_PL1_RW_PL0_RO: u8 = 0b100;
+
+#[derive(Clone, PartialEq, Debug, Default)]
 pub struct ArmPageAttributes {
     pub xn: bool,
     pub pxn: bool,
     pub permission_bits: u8,
+    pub attr_index: u8,
+    pub mair_attr: Option<u8>, // Set only if MAIR was provided
+    pub shareability: u8,
+    pub accessed: bool,
+    pub not_global: bool,
+    pub dbm: bool,
+    pub guarded: bool,
+    pub contiguous: bool,
+    // For stage-2 the permission bits hold S2AP and the attribute index holds MemAttr.
+    pub stage2: bool,
+}
+
+impl ArmPageAttributes {
+    pub fn get_memory_type(&self) -> Option<String> {
+        self.mair_attr.map(mair_attr_to_string)
+    }
+
+    pub fn is_device_memory(&self) -> Option<bool> {
+        self.mair_attr.map(|attr| attr & 0xf0 == 0)
+    }
+
+    pub fn get_shareability_str(&self) -> &'static str {
+        match self.shareability {
+            0b00 => "NSH",
+            0b10 => "OSH",
+            0b11 => "ISH",
+            _ => "RES",
+        }
+    }
+}
+
+fn cacheability_to_str(attr: u8) -> &'static str {
+    match attr {
+        0b0100 => "NC",
+        0b0000..=0b0011 => "WT-T",
+        0b0101..=0b0111 => "WB-T",
+        0b1000..=0b1011 => "WT",
+        _ => "WB",
+    }
 }
 
-#[derive(Clone)]
+pub fn mair_attr_to_string(attr: u8) -> String {
+    let outer = attr >> 4;
+    let inner = attr & 0xf;
+    if outer == 0 {
+        match inner {
+            0b0000 => String::from("Device-nGnRnE"),
+            0b0100 => String::from("Device-nGnRE"),
+            0b1000 => String::from("Device-nGRE"),
+            0b1100 => String::from("Device-GRE"),
+            _ => format!("Device-Unknown({:#x})", attr),
+        }
+    } else if attr == 0xf0 {
+        String::from("Normal-Tagged")
+    } else if inner == 0 {
+        format!("Normal-Unknown({:#x})", attr)
+    } else if inner == outer {
+        format!("Normal-{}", cacheability_to_str(inner))
+    } else {
+        format!(
+            "Normal-I{}-O{}",
+            cacheability_to_str(inner),
+            cacheability_to_str(outer)
+        )
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
 pub struct ArmPageRange {
     pub va: u64,
     pub extent: u64,
@@ -56,19 +185,35 @@ pub struct ArmPageRange {
 
 impl ArmPageRange {
     pub fn is_user_readable(&self) -> bool {
-        self.attr.permission_bits == 0b11 || self.attr.permission_bits == 0b01
+        if self.attr.stage2 {
+            return self.attr.permission_bits & 0b01 != 0;
+        }
+        self.attr.permission_bits == 0b11
+            || self.attr.permission_bits == 0b01
+            || self.attr.permission_bits == PERMISSION_BITS_PL1_RW_PL0_RO
     }
 
     pub fn is_kernel_readable(&self) -> bool {
+        if self.attr.stage2 {
+            return self.attr.permission_bits & 0b01 != 0;
+        }
         true
     }
 
     pub fn is_user_writeable(&self) -> bool {
+        if self.attr.stage2 {
+            return self.attr.permission_bits & 0b10 != 0;
+        }
         self.attr.permission_bits == 0b01
     }
 
     pub fn is_kernel_writeable(&self) -> bool {
-        self.attr.permission_bits == 0b01 || self.attr.permission_bits == 0b00
+        if self.attr.stage2 {
+            return self.attr.permission_bits & 0b10 != 0;
+        }
+        self.attr.permission_bits == 0b01
+            || self.attr.permission_bits == 0b00
+            || self.attr.permission_bits == PERMISSION_BITS_PL1_RW_PL0_RO
     }
 
     pub fn is_user_executable(&self) -> bool {
@@ -80,20 +225,23 @@ impl ArmPageRange {
     }
 
     pub fn is_extendable_by(&self, va: u64, attr: &ArmPageAttributes) -> bool {
-        return self.get_va() + self.get_va_extent() == va && self.attr == *attr;
+        self.get_va() + self.get_va_extent() == va
+            && self.attr.xn == attr.xn
+            && self.attr.pxn == attr.pxn
+            && self.attr.permission_bits == attr.permission_bits
+            && self.attr.attr_index == attr.attr_index
+            && self.attr.mair_attr == attr.mair_attr
+            && self.attr.stage2 == attr.stage2
     }
 
     pub fn extend_by(&mut self, next_extent: u64, next_phys: u64) {
         self.extent += next_extent;
-        let mut last = self.phys_ranges.last_mut().unwrap();
+        let last = self.phys_ranges.last_mut().unwrap();
         if last.phys_base + last.phys_extent == next_phys {
             last.phys_extent += next_extent;
-        } else if last.phys_base <= next_phys
-            && (next_phys + next_extent) <= (last.phys_base + last.phys_extent)
-        {
-            // Don't do anything since the range is already included and the range is considered semantically the same.
-            return;
         } else {
+            // Every leaf is kept, even if its frame is already mapped, so that the physical
+            // ranges describe the virtual range in order.
             self.phys_ranges
                 .push(PhysRange::new(next_phys, next_extent))
         }
@@ -104,6 +252,7 @@ impl ArmPageRange {
 struct LevelRangeInfo {
     bit_start_incl: u8,
     block_size: u64,
+    num_entries: usize,
 }
 
 #[derive(Clone, Debug)]
@@ -122,14 +271,6 @@ impl LevelRanges {
 }
 
 impl Granularity {
-    fn get_block_size(self) -> usize {
-        match self {
-            Granularity::Pt4k => 0x1000,
-            Granularity::Pt16k => 0x4000,
-            Granularity::Pt64k => 0x10000,
-        }
-    }
-
     fn get_bit_start(self) -> u8 {
         match self {
             Granularity::Pt4k => 12u8,
@@ -159,11 +300,41 @@ impl Granularity {
             ranges.push(LevelRangeInfo {
                 bit_start_incl: bit_start_incl,
                 block_size: block_size,
+                num_entries: 1usize << (bit_end_incl - bit_start_incl + 1),
             });
         }
         ranges.reverse();
         LevelRanges { levels: ranges }
     }
+
+    // The stage-2 walk may start at a lower level and concatenate up to 16 tables at the root.
+    fn get_stage2_level_ranges(self, ipa_size: u8, start_level: u8) -> Option<LevelRanges> {
+        let start = self.get_bit_start();
+        let bits_per_level = self.get_num_bits_per_level();
+        if start_level > 3 {
+            return None;
+        }
+        let root_bit_start = start + bits_per_level * (3 - start_level);
+        if ipa_size <= root_bit_start || ipa_size - root_bit_start > bits_per_level + 4 {
+            return None;
+        }
+        let levels = (start_level..=3)
+            .map(|level| {
+                let bit_start_incl = start + bits_per_level * (3 - level);
+                let num_bits = if level == start_level {
+                    ipa_size - root_bit_start
+                } else {
+                    bits_per_level
+                };
+                LevelRangeInfo {
+                    bit_start_incl: bit_start_incl,
+                    block_size: 1u64 << bit_start_incl,
+                    num_entries: 1usize << num_bits,
+                }
+            })
+            .collect();
+        Some(LevelRanges { levels: levels })
+    }
 }
 
 struct TablePointerEntry {
@@ -175,6 +346,92 @@ struct TablePointerEntry {
     level: u8,
 }
 
+// APTable[1] removes write access at all levels and APTable[0] removes access from EL0.
+fn apply_table_permissions(leaf_permission_bits: u8, table_permission_bits: u8) -> u8 {
+    let mut permission_bits = leaf_permission_bits | (table_permission_bits & 0b10);
+    if table_permission_bits & 0b01 != 0 {
+        permission_bits &= !0b01;
+    }
+    permission_bits
+}
+
+fn get_stage1_leaf_attributes(
+    context: &ArmContext,
+    table: &TablePointerEntry,
+    raw_entry: u64,
+) -> ArmPageAttributes {
+    let has_bit = |bit_loc: u8| ((raw_entry >> bit_loc) & 1_u64) == 1_u64;
+    let permissions = ((raw_entry >> 6) & 0x3) as u8;
+    let (permissions, xn, pxn) = if context.hierarchical_permissions {
+        (
+            apply_table_permissions(permissions, table.permission_bits),
+            has_bit(54) || table.xn,
+            has_bit(53) || table.pxn,
+        )
+    } else {
+        (permissions, has_bit(54), has_bit(53))
+    };
+    let attr_index = ((raw_entry >> 2) & 0x7) as u8;
+    ArmPageAttributes {
+        xn: xn,
+        pxn: pxn,
+        permission_bits: permissions,
+        attr_index: attr_index,
+        mair_attr: context
+            .mair
+            .map(|mair| ((mair >> (attr_index * 8)) & 0xff) as u8),
+        shareability: ((raw_entry >> 8) & 0x3) as u8,
+        accessed: has_bit(10),
+        not_global: has_bit(11),
+        dbm: has_bit(51),
+        guarded: has_bit(50),
+        contiguous: has_bit(52),
+        stage2: false,
+    }
+}
+
+// Stage-2 MemAttr is converted to the MAIR encoding so that both stages print the same way.
+fn stage2_mem_attr_to_mair_attr(mem_attr: u8) -> u8 {
+    let cacheability = |bits: u8| match bits {
+        0b01 => 0b0100,
+        0b10 => 0b1000,
+        0b11 => 0b1100,
+        _ => 0b0000,
+    };
+    let outer = mem_attr >> 2;
+    let inner = mem_attr & 0x3;
+    if outer == 0 {
+        inner << 2
+    } else {
+        cacheability(outer) << 4 | cacheability(inner)
+    }
+}
+
+fn get_stage2_leaf_attributes(raw_entry: u64) -> ArmPageAttributes {
+    let has_bit = |bit_loc: u8| ((raw_entry >> bit_loc) & 1_u64) == 1_u64;
+    let mem_attr = ((raw_entry >> 2) & 0xf) as u8;
+    // XN[1:0], where XN[0] only has an effect if FEAT_XNX is implemented.
+    let (xn, pxn) = match (raw_entry >> 53) & 0x3 {
+        0b00 => (false, false),
+        0b01 => (false, true),
+        0b10 => (true, true),
+        _ => (true, false),
+    };
+    ArmPageAttributes {
+        xn: xn,
+        pxn: pxn,
+        permission_bits: ((raw_entry >> 6) & 0x3) as u8,
+        attr_index: mem_attr,
+        mair_attr: Some(stage2_mem_attr_to_mair_attr(mem_attr)),
+        shareability: ((raw_entry >> 8) & 0x3) as u8,
+        accessed: has_bit(10),
+        dbm: has_bit(51),
+        contiguous: has_bit(52),
+        stage2: true,
+        ..Default::default()
+    }
+}
+
 fn parse_block_arm64(
     context: &ArmContext,
     memory: &mut dyn MemoryView,
@@ -182,10 +439,36 @@ fn parse_block_arm64(
     level_ranges: &LevelRanges,
     pages: &mut Vec<ArmPageRange>,
 ) -> Result<(), Error> {
-    let block_size = context.granularity.get_block_size();
-    let block = memory.read_block(table.base_address as usize, block_size)?;
     let level_info = level_ranges.get_level_info(table.level);
+    // The top level table may have fewer entries than the granule allows.
+    let block = memory.read_block(table.base_address as usize, level_info.num_entries * 8)?;
+
+    let block_size = 8;
+    for block_index in 0..block.len() / block_size {
+        let raw_entry = unsafe { *((block.as_ptr() as *const u64).add(block_index)) };
+        parse_entry_arm64(
+            context,
+            memory,
+            table,
+            level_ranges,
+            block_index,
+            raw_entry,
+            pages,
+        )?;
+    }
+    Ok(())
+}
 
+fn parse_entry_arm64(
+    context: &ArmContext,
+    memory: &mut dyn MemoryView,
+    table: &TablePointerEntry,
+    level_ranges: &LevelRanges,
+    block_index: usize,
+    raw_entry: u64,
+    pages: &mut Vec<ArmPageRange>,
+) -> Result<(), Error> {
+    let level_info = level_ranges.get_level_info(table.level);
     let mask_range = |(a_inclusive, b_inclusive): (u8, u8)| {
         let mask_to_zero = |a_inclusive: u8| (1_u64 << a_inclusive) - 1_u64;
         mask_to_zero(a_inclusive) ^ mask_to_zero(b_inclusive)
@@ -195,84 +478,254 @@ fn parse_block_arm64(
         value & mask_range((a_inclusive, b_inclusive))
     };
 
-    let block_size = 8;
-    for block_index in 0..block.len() / block_size {
-        let raw_entry = unsafe { *((block.as_ptr() as *const u64).add(block_index)) };
-        let has_bit = |bit_loc: u8| ((raw_entry >> bit_loc) & 1_u64) == 1_u64;
-        let valid = has_bit(0);
-        if !valid {
-            continue;
-        }
-        let contiguous_bit = has_bit(52);
-        let table_pointer = has_bit(1); // It could be a table entry or a table descriptor.
-
-        let va_contribution = (block_index as u64) << level_info.bit_start_incl;
-        let va = table.va | va_contribution;
-        let base_address = extract_bits_no_shift(raw_entry, 47, 12);
-
-        // TODO: do we have to propagate permission bit from the parent table? Most likely yes
-        if (table_pointer && contiguous_bit)
-            || !table_pointer
-            || (table.level + 1) as usize == level_ranges.get_num_levels()
-        {
-            // this is a leaf page
-            let permissions = ((raw_entry >> 6) & 0x3) as u8;
-            let xn = has_bit(54) || table.xn;
-            let pxn = has_bit(53) || table.pxn;
-            let attr = ArmPageAttributes {
-                xn: xn,
-                pxn: pxn,
-                permission_bits: permissions,
-            };
-            if let Some(previous_page) = pages.last_mut() {
-                if previous_page.is_extendable_by(va, &attr) {
-                    previous_page.extend_by(level_info.block_size, base_address);
-                    continue;
-                }
-            }
-            let entry = ArmPageRange {
-                va: va,
-                extent: level_info.block_size,
-                phys_ranges: vec![PhysRange::new(base_address, level_info.block_size)],
-                attr: attr,
-            };
-            pages.push(entry);
-        } else {
-            // this is table
-            let permissions = ((raw_entry >> 61) & 0x3) as u8;
-            let xn = has_bit(60) | table.xn;
-            let pxn = has_bit(59) | table.pxn;
-
-            let table = TablePointerEntry {
-                va: va,
-                base_address: base_address,
-                xn: xn,
-                pxn: pxn,
-                permission_bits: permissions,
-                level: table.level + 1,
-            };
-            parse_block_arm64(context, memory, &table, level_ranges, pages)?;
+    let has_bit = |bit_loc: u8| ((raw_entry >> bit_loc) & 1_u64) == 1_u64;
+    let valid = has_bit(0);
+    if !valid {
+        return Ok(());
+    }
+    let table_pointer = has_bit(1); // It could be a table entry or a table descriptor.
+
+    let va_contribution = (block_index as u64) << level_info.bit_start_incl;
+    let va = table.va | va_contribution;
+    let base_address = extract_bits_no_shift(raw_entry, 47, 12);
+
+    // The contiguous hint is ignored for table descriptors.
+    if !table_pointer || (table.level + 1) as usize == level_ranges.get_num_levels() {
+        // this is a leaf page
+        let attr = match context.flavour {
+            ArmFlavour::Arm64Stage2 => get_stage2_leaf_attributes(raw_entry),
+            _ => get_stage1_leaf_attributes(context, table, raw_entry),
+        };
+        push_page(pages, va, level_info.block_size, base_address, attr);
+    } else {
+        // this is table, APTable only ever removes permissions so they accumulate
+        let (permissions, xn, pxn) = match context.flavour {
+            // Stage-2 table descriptors have no hierarchical attributes.
+            ArmFlavour::Arm64Stage2 => (0, false, false),
+            _ => (
+                ((raw_entry >> 61) & 0x3) as u8 | table.permission_bits,
+                has_bit(60) | table.xn,
+                has_bit(59) | table.pxn,
+            ),
+        };
+
+        let table = TablePointerEntry {
+            va: va,
+            base_address: base_address,
+            xn: xn,
+            pxn: pxn,
+            permission_bits: permissions,
+            level: table.level + 1,
+        };
+        parse_block_arm64(context, memory, &table, level_ranges, pages)?;
+    }
+    Ok(())
+}
+
+fn push_page(
+    pages: &mut Vec<ArmPageRange>,
+    va: u64,
+    extent: u64,
+    pa: u64,
+    attr: ArmPageAttributes,
+) {
+    if let Some(previous_page) = pages.last_mut() {
+        if previous_page.is_extendable_by(va, &attr) {
+            previous_page.extend_by(extent, pa);
+            return;
         }
     }
+    pages.push(ArmPageRange {
+        va: va,
+        extent: extent,
+        phys_ranges: vec![PhysRange::new(pa, extent)],
+        attr: attr,
+    });
+}
+
+const ARM32_SECTION_SIZE: u64 = 1 << 20;
+const ARM32_SMALL_PAGE_SIZE: u64 = 1 << 12;
+
+// Returns None if the access is never permitted.
+fn arm32_get_permission_bits(dacr: u32, domain: u8, ap: u8) -> Option<u8> {
+    match (dacr >> (domain * 2)) & 0b11 {
+        // Client, accesses are checked against AP[2:0]
+        0b01 => match ap {
+            0b001 => Some(0b00),
+            0b010 => Some(PERMISSION_BITS_PL1_RW_PL0_RO),
+            0b011 => Some(0b01),
+            0b101 => Some(0b10),
+            0b110 | 0b111 => Some(0b11),
+            _ => None,
+        },
+        // Manager, accesses are not checked against AP[2:0]
+        0b11 => Some(0b01),
+        _ => None,
+    }
+}
+
+fn parse_arm32_page_table(
+    context: &ArmContext,
+    memory: &mut dyn MemoryView,
+    va: u64,
+    base_address: u64,
+    domain: u8,
+    pxn: bool,
+    pages: &mut Vec<ArmPageRange>,
+) -> Result<(), Error> {
+    let block = memory.read_block(base_address as usize, 256 * 4)?;
+    for block_index in 0..256usize {
+        let raw_entry = u32::from_le_bytes(
+            block[block_index * 4..(block_index + 1) * 4]
+                .try_into()
+                .unwrap(),
+        );
+        let has_bit = |bit_loc: u8| ((raw_entry >> bit_loc) & 1) == 1;
+        let ap = (((raw_entry >> 9) & 1) << 2 | ((raw_entry >> 4) & 0b11)) as u8;
+        let (address, xn) = match raw_entry & 0b11 {
+            0b00 => continue,
+            // Large pages are repeated 16 times in the table.
+            0b01 => (
+                ((raw_entry & 0xffff0000) as u64)
+                    | (((block_index & 0xf) as u64) * ARM32_SMALL_PAGE_SIZE),
+                has_bit(15),
+            ),
+            _ => ((raw_entry & 0xfffff000) as u64, has_bit(0)),
+        };
+        let permission_bits = match arm32_get_permission_bits(context.dacr, domain, ap) {
+            Some(permission_bits) => permission_bits,
+            None => continue,
+        };
+        // There is no access flag when SCTLR.AFE is clear.
+        let attr = ArmPageAttributes {
+            xn: xn,
+            pxn: pxn,
+            permission_bits: permission_bits,
+            accessed: true,
+            not_global: has_bit(11),
+            ..Default::default()
+        };
+        let page_va = va | ((block_index as u64) * ARM32_SMALL_PAGE_SIZE);
+        push_page(pages, page_va, ARM32_SMALL_PAGE_SIZE, address, attr);
+    }
     Ok(())
 }
 
-fn parse_arm64(
+fn parse_arm32_short(
     context: &ArmContext,
     memory: &mut dyn MemoryView,
     pa: u64,
 ) -> Result<Vec<ArmPageRange>, Error> {
-    let ranges = context
-        .granularity
-        .get_level_ranges(context.virtual_address_space_size);
-    let root = TablePointerEntry {
-        va: !(((context.top_bit as u64) << context.virtual_address_space_size) - 1u64),
+    // TTBR0 covers the bottom 2^(32-N) bytes and TTBR1 the rest.
+    let num_entries_ttbr0 = 1usize << (context.virtual_address_space_size - 20);
+    let (start_index, end_index) = if context.top_bit == 1 {
+        (num_entries_ttbr0, 4096)
+    } else {
+        (0, num_entries_ttbr0)
+    };
+    let block = memory.read_block(pa as usize, end_index * 4)?;
+    let mut page_entries = Vec::new();
+
+    for block_index in start_index..end_index {
+        let raw_entry = u32::from_le_bytes(
+            block[block_index * 4..(block_index + 1) * 4]
+                .try_into()
+                .unwrap(),
+        );
+        let has_bit = |bit_loc: u8| ((raw_entry >> bit_loc) & 1) == 1;
+        let va = (block_index as u64) * ARM32_SECTION_SIZE;
+        let domain = ((raw_entry >> 5) & 0xf) as u8;
+        match raw_entry & 0b11 {
+            0b00 => continue,
+            0b01 => {
+                // Intentionally ignore errors
+                let _ = parse_arm32_page_table(
+                    context,
+                    memory,
+                    va,
+                    (raw_entry & 0xfffffc00) as u64,
+                    domain,
+                    has_bit(2),
+                    &mut page_entries,
+                );
+            }
+            _ => {
+                let ap = (((raw_entry >> 15) & 1) << 2 | ((raw_entry >> 10) & 0b11)) as u8;
+                let (address, domain) = if has_bit(18) {
+                    // Supersections are repeated 16 times in the table and always use domain 0.
+                    let base = ((raw_entry & 0xff000000) as u64)
+                        | ((((raw_entry >> 20) & 0xf) as u64) << 32)
+                        | ((((raw_entry >> 5) & 0xf) as u64) << 36);
+                    (
+                        base | (((block_index & 0xf) as u64) * ARM32_SECTION_SIZE),
+                        0,
+                    )
+                } else {
+                    ((raw_entry & 0xfff00000) as u64, domain)
+                };
+                let permission_bits = match arm32_get_permission_bits(context.dacr, domain, ap) {
+                    Some(permission_bits) => permission_bits,
+                    None => continue,
+                };
+                let attr = ArmPageAttributes {
+                    xn: has_bit(4),
+                    pxn: has_bit(0),
+                    permission_bits: permission_bits,
+                    accessed: true,
+                    not_global: has_bit(17),
+                    ..Default::default()
+                };
+                push_page(&mut page_entries, va, ARM32_SECTION_SIZE, address, attr);
+            }
+        }
+    }
+    Ok(page_entries)
+}
+
+fn get_arm64_level_ranges(context: &ArmContext) -> Result<LevelRanges, Error> {
+    match context.flavour {
+        ArmFlavour::Arm64Stage2 => context
+            .granularity
+            .get_stage2_level_ranges(
+                context.virtual_address_space_size,
+                context.stage2_start_level,
+            )
+            .ok_or(Error::UnsupportedTranslationMode),
+        _ => Ok(context
+            .granularity
+            .get_level_ranges(context.virtual_address_space_size)),
+    }
+}
+
+fn get_arm64_root_va(context: &ArmContext) -> u64 {
+    if context.top_bit == 0 {
+        0
+    } else {
+        match context.flavour {
+            ArmFlavour::Arm64 => !((1u64 << context.virtual_address_space_size) - 1u64),
+            _ => (1u64 << 32) - (1u64 << context.virtual_address_space_size),
+        }
+    }
+}
+
+fn get_arm64_root(context: &ArmContext, pa: u64) -> TablePointerEntry {
+    TablePointerEntry {
+        va: get_arm64_root_va(context),
         base_address: pa,
         xn: false,
         pxn: false,
         permission_bits: 0,
         level: 0,
-    };
+    }
+}
+
+fn parse_arm64(
+    context: &ArmContext,
+    memory: &mut dyn MemoryView,
+    pa: u64,
+) -> Result<Vec<ArmPageRange>, Error> {
+    let ranges = get_arm64_level_ranges(context)?;
+    let root = get_arm64_root(context, pa);
     let mut page_entries = Vec::new();
 
     parse_block_arm64(&context, memory, &root, &ranges, &mut page_entries)?;
@@ -280,17 +733,284 @@ fn parse_arm64(
     Ok(page_entries)
 }
 
+// Walks the subtrees of the root table entries in parallel, see x86::collect_pages_parallel.
+fn parse_arm64_parallel<M: MemoryView + Clone>(
+    context: &ArmContext,
+    memory: &M,
+    pa: u64,
+) -> Result<Vec<ArmPageRange>, Error> {
+    let ranges = get_arm64_level_ranges(context)?;
+    let root = get_arm64_root(context, pa);
+    let root_block = memory
+        .clone()
+        .read_block(pa as usize, ranges.get_level_info(0).num_entries * 8)?;
+    let valid_entries: Vec<(usize, u64)> = root_block
+        .chunks_exact(8)
+        .map(|entry| u64::from_le_bytes(entry.try_into().unwrap()))
+        .enumerate()
+        .filter(|(_, raw_entry)| raw_entry & 1 == 1)
+        .collect();
+
+    let subtrees: Vec<Result<Vec<ArmPageRange>, Error>> = valid_entries
+        .par_iter()
+        .map_with(memory.clone(), |memory, &(index, raw_entry)| {
+            let mut pages = vec![];
+            parse_entry_arm64(
+                context, memory, &root, &ranges, index, raw_entry, &mut pages,
+            )?;
+            Ok(pages)
+        })
+        .collect();
+
+    // The physical ranges hold every leaf in VA order, so extending by them one by one is
+    // the same as extending leaf by leaf.
+    let mut pages: Vec<ArmPageRange> = vec![];
+    for subtree_pages in subtrees {
+        let mut subtree_pages = subtree_pages?.into_iter().peekable();
+        if let Some(previous_page) = pages.last_mut() {
+            if let Some(next_page) = subtree_pages.next_if(|next_page| {
+                previous_page.is_extendable_by(next_page.va, &next_page.attr)
+            }) {
+                for phys_range in next_page.phys_ranges {
+                    previous_page.extend_by(phys_range.phys_extent, phys_range.phys_base);
+                }
+            }
+        }
+        pages.extend(subtree_pages);
+    }
+    Ok(pages)
+}
+
+// LPAE always uses a 4K granule and a 32-bit input address.
+fn get_lpae_context(context: &ArmContext) -> ArmContext {
+    let mut lpae_context = *context;
+    lpae_context.granularity = Granularity::Pt4k;
+    lpae_context.virtual_address_space_size = std::cmp::min(context.virtual_address_space_size, 32);
+    lpae_context
+}
+
 pub fn collect_pages(
     context: &ArmContext,
     memory: &mut dyn MemoryView,
     pa: u64,
 ) -> Result<Vec<ArmPageRange>, Error> {
     match context.flavour {
-        ArmFlavour::Arm64 => {
-            return parse_arm64(&context, memory, pa);
+        ArmFlavour::Arm64 | ArmFlavour::Arm64Stage2 => parse_arm64(context, memory, pa),
+        ArmFlavour::Arm32Lpae => parse_arm64(&get_lpae_context(context), memory, pa),
+        ArmFlavour::Arm32 => parse_arm32_short(context, memory, pa),
+    }
+}
+
+// Like collect_pages, but the entries of the root table are walked in parallel. Each thread
+// reads through its own clone of the memory view. The short-descriptor format is walked
+// sequentially.
+pub fn collect_pages_parallel<M: MemoryView + Clone>(
+    context: &ArmContext,
+    memory: &M,
+    pa: u64,
+) -> Result<Vec<ArmPageRange>, Error> {
+    match context.flavour {
+        ArmFlavour::Arm64 | ArmFlavour::Arm64Stage2 => parse_arm64_parallel(context, memory, pa),
+        ArmFlavour::Arm32Lpae => parse_arm64_parallel(&get_lpae_context(context), memory, pa),
+        ArmFlavour::Arm32 => parse_arm32_short(context, &mut memory.clone(), pa),
+    }
+}
+
+// VTTBR_EL2.BADDR, the VMID is in the upper bits.
+pub fn vttbr_to_root_pa(vttbr: u64) -> u64 {
+    vttbr & 0x0000_ffff_ffff_fffe
+}
+
+pub fn collect_stage2_pages_from_vttbr(
+    memory: &mut dyn MemoryView,
+    vttbr: u64,
+    vtcr: u64,
+) -> Result<Vec<ArmPageRange>, Error> {
+    collect_pages(
+        &ArmContext::from_vtcr(vtcr)?,
+        memory,
+        vttbr_to_root_pa(vttbr),
+    )
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum ArmWalkEntry {
+    Invalid,
+    // APTable, UXNTable and PXNTable of this descriptor
+    Table {
+        next_table_pa: u64,
+        permission_bits: u8,
+        xn: bool,
+        pxn: bool,
+    },
+    Leaf {
+        output_pa: u64,
+        block_size: u64,
+        attr: ArmPageAttributes,
+    },
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct ArmWalkStep {
+    pub level: u8, // Architectural level, i.e. the last level is always 3
+    pub index: usize,
+    pub table_pa: u64,
+    pub raw_entry: u64,
+    pub entry: ArmWalkEntry,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct ArmTranslatedAddress {
+    pub pa: u64,
+    pub page_size: u64,
+    pub attr: ArmPageAttributes, // Includes the hierarchical permissions if enabled
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct ArmWalkFault {
+    pub level: u8,
+    pub reason: WalkFaultReason,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct ArmTranslation {
+    pub va: u64,
+    pub steps: Vec<ArmWalkStep>,
+    pub result: Result<ArmTranslatedAddress, ArmWalkFault>,
+}
+
+fn translate_arm64(
+    context: &ArmContext,
+    memory: &mut dyn MemoryView,
+    pa: u64,
+    va: u64,
+) -> Result<ArmTranslation, Error> {
+    let ranges = get_arm64_level_ranges(context)?;
+    let num_levels = ranges.get_num_levels();
+    let first_level = (4 - num_levels) as u8;
+    let root_va = get_arm64_root_va(context);
+    let mut steps = vec![];
+    let fault = |level: u8, reason: WalkFaultReason| Err(ArmWalkFault { level, reason });
+
+    let va_offset = va.wrapping_sub(root_va);
+    if va_offset >> context.virtual_address_space_size != 0 {
+        return Ok(ArmTranslation {
+            va,
+            steps,
+            result: fault(first_level, WalkFaultReason::AddressOutOfRange),
+        });
+    }
+
+    let mut table = TablePointerEntry {
+        va: root_va,
+        base_address: pa,
+        xn: false,
+        pxn: false,
+        permission_bits: 0,
+        level: 0,
+    };
+    let result = loop {
+        let level_info = ranges.get_level_info(table.level);
+        let level = first_level + table.level;
+        let index =
+            ((va_offset >> level_info.bit_start_incl) as usize) & (level_info.num_entries - 1);
+        let raw_entry = match memory.read_block(table.base_address as usize + index * 8, 8) {
+            Ok(block) => u64::from_le_bytes(block[..].try_into().unwrap()),
+            Err(_) => break fault(level, WalkFaultReason::TableNotReadable),
+        };
+        let has_bit = |bit_loc: u8| ((raw_entry >> bit_loc) & 1_u64) == 1_u64;
+        let last_level = (table.level + 1) as usize == num_levels;
+        let address = raw_entry & 0x0000_ffff_ffff_f000;
+        let (entry, reason) = if !has_bit(0) {
+            (ArmWalkEntry::Invalid, Some(WalkFaultReason::NotPresent))
+        } else if last_level && !has_bit(1) {
+            (
+                ArmWalkEntry::Invalid,
+                Some(WalkFaultReason::ReservedEncoding),
+            )
+        } else if !last_level && has_bit(1) {
+            let entry = match context.flavour {
+                ArmFlavour::Arm64Stage2 => ArmWalkEntry::Table {
+                    next_table_pa: address,
+                    permission_bits: 0,
+                    xn: false,
+                    pxn: false,
+                },
+                _ => ArmWalkEntry::Table {
+                    next_table_pa: address,
+                    permission_bits: ((raw_entry >> 61) & 0x3) as u8,
+                    xn: has_bit(60),
+                    pxn: has_bit(59),
+                },
+            };
+            (entry, None)
+        } else {
+            let attr = match context.flavour {
+                ArmFlavour::Arm64Stage2 => get_stage2_leaf_attributes(raw_entry),
+                _ => get_stage1_leaf_attributes(context, &table, raw_entry),
+            };
+            let entry = ArmWalkEntry::Leaf {
+                output_pa: address & !(level_info.block_size - 1),
+                block_size: level_info.block_size,
+                attr,
+            };
+            (entry, None)
+        };
+        steps.push(ArmWalkStep {
+            level,
+            index,
+            table_pa: table.base_address,
+            raw_entry,
+            entry: entry.clone(),
+        });
+        if let Some(reason) = reason {
+            break fault(level, reason);
         }
-        ArmFlavour::Arm32 => {
-            unreachable!();
+
+        match entry {
+            ArmWalkEntry::Table {
+                next_table_pa,
+                permission_bits,
+                xn,
+                pxn,
+            } => {
+                table = TablePointerEntry {
+                    va: table.va | ((index as u64) << level_info.bit_start_incl),
+                    base_address: next_table_pa,
+                    xn: xn | table.xn,
+                    pxn: pxn | table.pxn,
+                    permission_bits: permission_bits | table.permission_bits,
+                    level: table.level + 1,
+                };
+            }
+            ArmWalkEntry::Leaf {
+                output_pa,
+                block_size,
+                attr,
+            } => {
+                break Ok(ArmTranslatedAddress {
+                    pa: output_pa | (va & (block_size - 1)),
+                    page_size: block_size,
+                    attr,
+                });
+            }
+            ArmWalkEntry::Invalid => unreachable!(),
         }
+    };
+    Ok(ArmTranslation { va, steps, result })
+}
+
+// Walks only the descriptors which are used to translate the given virtual address.
+// The short-descriptor format is not supported.
+pub fn translate(
+    context: &ArmContext,
+    memory: &mut dyn MemoryView,
+    pa: u64,
+    va: u64,
+) -> Result<ArmTranslation, Error> {
+    match context.flavour {
+        ArmFlavour::Arm64 | ArmFlavour::Arm64Stage2 => translate_arm64(context, memory, pa, va),
+        ArmFlavour::Arm32Lpae => translate_arm64(&get_lpae_context(context), memory, pa, va),
+        ArmFlavour::Arm32 => Err(Error::UnsupportedTranslationMode),
     }
 }
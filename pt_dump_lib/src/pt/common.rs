@@ -13,6 +13,15 @@ impl PhysRange {
     }
 }
 
+// Why a single address translation stopped.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum WalkFaultReason {
+    NotPresent,
+    TableNotReadable,
+    AddressOutOfRange,
+    ReservedEncoding,
+}
+
 #[derive(Debug)]
 pub enum Error {
     FailedToReadBlock,
@@ -32,5 +41,10 @@ pub enum Error {
     InvalidBlock,
     FailedToOpenFile,
 
+    UnsupportedTranslationMode,
+    UnmappedMemory,
+
+    InvalidSearchPattern,
+
     ResourceError,
 }
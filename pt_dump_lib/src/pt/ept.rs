@@ -0,0 +1,249 @@
+use crate::memory::memory::MemoryView;
+use crate::pt::common::{Error, PhysRange};
+
+const PAGE_SHIFT: u8 = 12;
+const BITS_PER_LEVEL: u8 = 9;
+const PAGE_SIZE: usize = 1 << PAGE_SHIFT;
+const ENTRY_SIZE: usize = 8;
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct EptPageAttributes {
+    pub readable: bool,
+    pub writeable: bool,
+    pub executable: bool, // Supervisor-mode execute if MBEC is enabled
+    pub user_executable: bool,
+    pub memory_type: u8,
+    pub ignore_pat: bool,
+    pub accessed: bool,
+    pub dirty: bool,
+    pub suppress_ve: bool,
+}
+
+impl EptPageAttributes {
+    pub fn get_memory_type_str(&self) -> &'static str {
+        match self.memory_type {
+            0 => "UC",
+            1 => "WC",
+            4 => "WT",
+            5 => "WP",
+            6 => "WB",
+            _ => "RES",
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct EptPageRange {
+    pub gpa: u64,
+    pub extent: u64,
+    pub phys_ranges: Vec<PhysRange>, // Host physical addresses
+    pub attr: EptPageAttributes,
+}
+
+impl EptPageRange {
+    pub fn new(gpa: u64, extent: u64, attr: EptPageAttributes, ranges: Vec<PhysRange>) -> Self {
+        Self {
+            gpa,
+            extent,
+            phys_ranges: ranges,
+            attr,
+        }
+    }
+
+    pub fn get_attributes(&self) -> &EptPageAttributes {
+        &self.attr
+    }
+
+    fn is_extendable_by(&self, next_gpa: u64, next_attr: &EptPageAttributes) -> bool {
+        (self.gpa + self.extent) == next_gpa
+            && self.attr.readable == next_attr.readable
+            && self.attr.writeable == next_attr.writeable
+            && self.attr.executable == next_attr.executable
+            && self.attr.user_executable == next_attr.user_executable
+            && self.attr.memory_type == next_attr.memory_type
+    }
+
+    fn extend_by(&mut self, next_extent: u64, next_phys: u64) {
+        self.extent += next_extent;
+        let last = self.phys_ranges.last_mut().unwrap();
+        if last.phys_base + last.phys_extent == next_phys {
+            last.phys_extent += next_extent;
+        } else {
+            // Every leaf is kept, even if its frame is already mapped, so that the physical
+            // ranges describe the virtual range in order.
+            self.phys_ranges
+                .push(PhysRange::new(next_phys, next_extent))
+        }
+    }
+}
+
+#[derive(Copy, Clone)]
+pub struct EptContext {
+    num_levels: u8,
+    mbec: bool,
+}
+
+impl EptContext {
+    pub fn new(num_levels: u8, mbec: bool) -> Self {
+        Self { num_levels, mbec }
+    }
+
+    // EPTP[5:3] holds the page-walk length minus one.
+    pub fn from_eptp(eptp: u64, mbec: bool) -> Self {
+        Self {
+            num_levels: (((eptp >> 3) & 0x7) + 1) as u8,
+            mbec,
+        }
+    }
+}
+
+pub fn eptp_to_root_pa(eptp: u64) -> u64 {
+    eptp & 0x000f_ffff_ffff_f000
+}
+
+// Permissions are ANDed over all levels of the walk.
+#[derive(Copy, Clone)]
+struct EptPermissions {
+    readable: bool,
+    writeable: bool,
+    executable: bool,
+    user_executable: bool,
+}
+
+impl EptPermissions {
+    fn and(&self, other: &EptPermissions) -> EptPermissions {
+        EptPermissions {
+            readable: self.readable && other.readable,
+            writeable: self.writeable && other.writeable,
+            executable: self.executable && other.executable,
+            user_executable: self.user_executable && other.user_executable,
+        }
+    }
+}
+
+struct TablePointerEntry {
+    gpa: u64,
+    base_address: u64,
+    level: u8, // Counts down to 0, which is the 4K level.
+    permissions: EptPermissions,
+}
+
+fn parse_table_ept(
+    context: &EptContext,
+    memory: &mut dyn MemoryView,
+    table: &TablePointerEntry,
+    pages: &mut Vec<EptPageRange>,
+) -> Result<(), Error> {
+    let block = memory.read_block(table.base_address as usize, PAGE_SIZE)?;
+    let bit_start = PAGE_SHIFT + BITS_PER_LEVEL * table.level;
+    let block_size = 1u64 << bit_start;
+
+    for index in 0..PAGE_SIZE / ENTRY_SIZE {
+        let raw_entry = u64::from_le_bytes(
+            block[index * ENTRY_SIZE..(index + 1) * ENTRY_SIZE]
+                .try_into()
+                .unwrap(),
+        );
+        let has_bit = |bit_loc: u8| ((raw_entry >> bit_loc) & 1_u64) == 1_u64;
+        let readable = has_bit(0);
+        let writeable = has_bit(1);
+        let executable = has_bit(2);
+        let user_executable = context.mbec && has_bit(10);
+        if !(readable || writeable || executable || user_executable) {
+            continue;
+        }
+        if writeable && !readable {
+            // EPT misconfiguration
+            continue;
+        }
+
+        let address = raw_entry & 0x000f_ffff_ffff_f000;
+        let gpa = table.gpa | ((index as u64) << bit_start);
+        let large_page = table.level > 0 && table.level < 3 && has_bit(7);
+        let permissions = table.permissions.and(&EptPermissions {
+            readable,
+            writeable,
+            executable,
+            // Without MBEC the execute bit applies to both modes.
+            user_executable: if context.mbec {
+                user_executable
+            } else {
+                executable
+            },
+        });
+
+        if table.level == 0 || large_page {
+            // The low address bits of large pages are reserved.
+            let address = address & !(block_size - 1);
+            let attr = EptPageAttributes {
+                readable: permissions.readable,
+                writeable: permissions.writeable,
+                executable: permissions.executable,
+                user_executable: permissions.user_executable,
+                memory_type: ((raw_entry >> 3) & 0x7) as u8,
+                ignore_pat: has_bit(6),
+                accessed: has_bit(8),
+                dirty: has_bit(9),
+                suppress_ve: has_bit(63),
+            };
+            if let Some(previous_page) = pages.last_mut() {
+                if previous_page.is_extendable_by(gpa, &attr) {
+                    previous_page.extend_by(block_size, address);
+                    continue;
+                }
+            }
+            pages.push(EptPageRange::new(
+                gpa,
+                block_size,
+                attr,
+                vec![PhysRange::new(address, block_size)],
+            ));
+        } else {
+            let next_table = TablePointerEntry {
+                gpa,
+                base_address: address,
+                level: table.level - 1,
+                permissions,
+            };
+            // Intentionally ignore errors from tables which cannot be read.
+            let _ = parse_table_ept(context, memory, &next_table, pages);
+        }
+    }
+    Ok(())
+}
+
+pub fn collect_pages(
+    context: &EptContext,
+    memory: &mut dyn MemoryView,
+    pa: u64,
+) -> Result<Vec<EptPageRange>, Error> {
+    if context.num_levels != 4 && context.num_levels != 5 {
+        return Err(Error::UnsupportedTranslationMode);
+    }
+    let root = TablePointerEntry {
+        gpa: 0,
+        base_address: pa,
+        level: context.num_levels - 1,
+        permissions: EptPermissions {
+            readable: true,
+            writeable: true,
+            executable: true,
+            user_executable: true,
+        },
+    };
+    let mut pages = Vec::new();
+    parse_table_ept(context, memory, &root, &mut pages)?;
+    Ok(pages)
+}
+
+pub fn collect_pages_from_eptp(
+    memory: &mut dyn MemoryView,
+    eptp: u64,
+    mbec: bool,
+) -> Result<Vec<EptPageRange>, Error> {
+    collect_pages(
+        &EptContext::from_eptp(eptp, mbec),
+        memory,
+        eptp_to_root_pa(eptp),
+    )
+}
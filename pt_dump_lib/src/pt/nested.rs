@@ -0,0 +1,127 @@
+use crate::memory::memory::MemoryView;
+use crate::memory::memory_virtual::VirtualMemoryView;
+use crate::pt::common::{Error, PhysRange};
+use crate::pt::page_range::{GenericPagePermissions, GenericPageRange};
+
+// The permissions are the intersection of the stage-1 and stage-2 permissions.
+#[derive(Clone, PartialEq, Debug)]
+pub struct NestedPageAttributes {
+    pub user_readable: bool,
+    pub user_writeable: bool,
+    pub user_executable: bool,
+    pub kernel_readable: bool,
+    pub kernel_writeable: bool,
+    pub kernel_executable: bool,
+}
+
+impl NestedPageAttributes {
+    fn intersect<S1: GenericPagePermissions, S2: GenericPagePermissions>(
+        stage1: &S1,
+        stage2: &S2,
+    ) -> Self {
+        Self {
+            user_readable: stage1.is_user_readable() && stage2.is_user_readable(),
+            user_writeable: stage1.is_user_writeable() && stage2.is_user_writeable(),
+            user_executable: stage1.is_user_executable() && stage2.is_user_executable(),
+            kernel_readable: stage1.is_kernel_readable() && stage2.is_kernel_readable(),
+            kernel_writeable: stage1.is_kernel_writeable() && stage2.is_kernel_writeable(),
+            kernel_executable: stage1.is_kernel_executable() && stage2.is_kernel_executable(),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct NestedPageRange {
+    pub gva: u64,
+    pub extent: u64,
+    pub gpa: u64, // Guest physical addresses are contiguous within a range
+    pub phys_ranges: Vec<PhysRange>, // Host physical addresses
+    pub attr: NestedPageAttributes,
+}
+
+impl NestedPageRange {
+    pub fn get_attributes(&self) -> &NestedPageAttributes {
+        &self.attr
+    }
+
+    fn is_extendable_by(&self, gva: u64, gpa: u64, attr: &NestedPageAttributes) -> bool {
+        self.gva + self.extent == gva && self.gpa + self.extent == gpa && self.attr == *attr
+    }
+
+    fn extend_by(&mut self, next_extent: u64, next_hpa: u64) {
+        self.extent += next_extent;
+        let last = self.phys_ranges.last_mut().unwrap();
+        if last.phys_base + last.phys_extent == next_hpa {
+            last.phys_extent += next_extent;
+        } else {
+            self.phys_ranges.push(PhysRange::new(next_hpa, next_extent))
+        }
+    }
+}
+
+// Runs the stage-1 walk over guest physical memory translated by the stage-2 ranges and
+// resolves every stage-1 mapping to host physical memory. Guest physical addresses which
+// are not mapped by stage-2 are left out.
+pub fn collect_nested_pages<S1, S2, F>(
+    host_memory: &mut dyn MemoryView,
+    stage2_ranges: &[S2],
+    collect_stage1: F,
+) -> Result<Vec<NestedPageRange>, Error>
+where
+    S1: GenericPageRange + GenericPagePermissions,
+    S2: GenericPageRange + GenericPagePermissions,
+    F: FnOnce(&mut dyn MemoryView) -> Result<Vec<S1>, Error>,
+{
+    let mut guest_memory = VirtualMemoryView::new(host_memory, stage2_ranges);
+    let stage1_ranges = collect_stage1(&mut guest_memory)?;
+
+    let mut pages: Vec<NestedPageRange> = vec![];
+    for stage1_range in stage1_ranges.iter() {
+        let mut gva = stage1_range.get_va_start();
+        for phys_range in stage1_range.get_phys_ranges() {
+            let mut gpa = phys_range.phys_base;
+            let gpa_end = phys_range.phys_base + phys_range.phys_extent;
+            while gpa < gpa_end {
+                let mapping = match guest_memory.find_mapping(gpa) {
+                    Some(mapping) => *mapping,
+                    None => {
+                        // Skip to the next mapped guest physical address.
+                        let mappings = guest_memory.get_mappings();
+                        let index = mappings.partition_point(|mapping| mapping.va <= gpa);
+                        let next_gpa = mappings
+                            .get(index)
+                            .map_or(gpa_end, |mapping| mapping.va.min(gpa_end));
+                        gva += next_gpa - gpa;
+                        gpa = next_gpa;
+                        continue;
+                    }
+                };
+                let extent = (mapping.extent - (gpa - mapping.va)).min(gpa_end - gpa);
+                let hpa = mapping.pa + (gpa - mapping.va);
+                let attr = NestedPageAttributes::intersect(
+                    stage1_range,
+                    &stage2_ranges[mapping.range_index],
+                );
+                let extended = match pages.last_mut() {
+                    Some(previous_page) if previous_page.is_extendable_by(gva, gpa, &attr) => {
+                        previous_page.extend_by(extent, hpa);
+                        true
+                    }
+                    _ => false,
+                };
+                if !extended {
+                    pages.push(NestedPageRange {
+                        gva,
+                        extent,
+                        gpa,
+                        phys_ranges: vec![PhysRange::new(hpa, extent)],
+                        attr,
+                    });
+                }
+                gva += extent;
+                gpa += extent;
+            }
+        }
+    }
+    Ok(pages)
+}
@@ -1,5 +1,8 @@
 use crate::pt::arm;
 use crate::pt::common;
+use crate::pt::ept;
+use crate::pt::nested;
+use crate::pt::riscv;
 use crate::pt::x86;
 
 pub trait GenericPage {
@@ -18,6 +21,24 @@ impl GenericPage for arm::ArmPageRange {
     }
 }
 
+impl GenericPage for riscv::RiscvPageRange {
+    fn get_va(&self) -> u64 {
+        self.va
+    }
+}
+
+impl GenericPage for ept::EptPageRange {
+    fn get_va(&self) -> u64 {
+        self.gpa
+    }
+}
+
+impl GenericPage for nested::NestedPageRange {
+    fn get_va(&self) -> u64 {
+        self.gva
+    }
+}
+
 /*
 impl From<arm::ArmPage> for arm::ArmPage {
     fn from(page: arm::ArmPage) -> Self {
@@ -59,3 +80,221 @@ impl GenericPageRange for arm::ArmPageRange {
         self.extent
     }
 }
+
+impl GenericPageRange for riscv::RiscvPageRange {
+    fn get_phys_ranges(&self) -> &Vec<common::PhysRange> {
+        &self.phys_ranges
+    }
+
+    fn get_va_start(&self) -> u64 {
+        self.va
+    }
+
+    fn get_va_extent(&self) -> u64 {
+        self.extent
+    }
+}
+
+impl GenericPageRange for ept::EptPageRange {
+    fn get_phys_ranges(&self) -> &Vec<common::PhysRange> {
+        &self.phys_ranges
+    }
+
+    fn get_va_start(&self) -> u64 {
+        self.gpa
+    }
+
+    fn get_va_extent(&self) -> u64 {
+        self.extent
+    }
+}
+
+impl GenericPageRange for nested::NestedPageRange {
+    fn get_phys_ranges(&self) -> &Vec<common::PhysRange> {
+        &self.phys_ranges
+    }
+
+    fn get_va_start(&self) -> u64 {
+        self.gva
+    }
+
+    fn get_va_extent(&self) -> u64 {
+        self.extent
+    }
+}
+
+// Effective access permissions of a mapping. For nested page tables "user" and "kernel"
+// refer to the guest privilege level.
+pub trait GenericPagePermissions {
+    fn is_user_readable(&self) -> bool;
+    fn is_user_writeable(&self) -> bool;
+    fn is_user_executable(&self) -> bool;
+    fn is_kernel_readable(&self) -> bool;
+    fn is_kernel_writeable(&self) -> bool;
+    fn is_kernel_executable(&self) -> bool;
+}
+
+impl GenericPagePermissions for x86::X86PageRange {
+    fn is_user_readable(&self) -> bool {
+        self.attributes.user
+    }
+
+    fn is_user_writeable(&self) -> bool {
+        self.attributes.user && self.attributes.writeable
+    }
+
+    fn is_user_executable(&self) -> bool {
+        self.attributes.user && !self.attributes.nx
+    }
+
+    fn is_kernel_readable(&self) -> bool {
+        true
+    }
+
+    fn is_kernel_writeable(&self) -> bool {
+        self.attributes.writeable
+    }
+
+    fn is_kernel_executable(&self) -> bool {
+        !self.attributes.nx
+    }
+}
+
+impl GenericPagePermissions for arm::ArmPageRange {
+    fn is_user_readable(&self) -> bool {
+        self.is_user_readable()
+    }
+
+    fn is_user_writeable(&self) -> bool {
+        self.is_user_writeable()
+    }
+
+    fn is_user_executable(&self) -> bool {
+        self.is_user_executable()
+    }
+
+    fn is_kernel_readable(&self) -> bool {
+        self.is_kernel_readable()
+    }
+
+    fn is_kernel_writeable(&self) -> bool {
+        self.is_kernel_writeable()
+    }
+
+    fn is_kernel_executable(&self) -> bool {
+        self.is_kernel_executable()
+    }
+}
+
+impl GenericPagePermissions for riscv::RiscvPageRange {
+    fn is_user_readable(&self) -> bool {
+        self.attr.user && self.attr.readable
+    }
+
+    fn is_user_writeable(&self) -> bool {
+        self.attr.user && self.attr.writeable
+    }
+
+    fn is_user_executable(&self) -> bool {
+        self.attr.user && self.attr.executable
+    }
+
+    fn is_kernel_readable(&self) -> bool {
+        self.attr.readable
+    }
+
+    fn is_kernel_writeable(&self) -> bool {
+        self.attr.writeable
+    }
+
+    fn is_kernel_executable(&self) -> bool {
+        // Supervisor mode can never execute user pages.
+        self.attr.executable && !self.attr.user
+    }
+}
+
+impl GenericPagePermissions for ept::EptPageRange {
+    fn is_user_readable(&self) -> bool {
+        self.attr.readable
+    }
+
+    fn is_user_writeable(&self) -> bool {
+        self.attr.writeable
+    }
+
+    fn is_user_executable(&self) -> bool {
+        self.attr.user_executable
+    }
+
+    fn is_kernel_readable(&self) -> bool {
+        self.attr.readable
+    }
+
+    fn is_kernel_writeable(&self) -> bool {
+        self.attr.writeable
+    }
+
+    fn is_kernel_executable(&self) -> bool {
+        self.attr.executable
+    }
+}
+
+impl GenericPagePermissions for nested::NestedPageRange {
+    fn is_user_readable(&self) -> bool {
+        self.attr.user_readable
+    }
+
+    fn is_user_writeable(&self) -> bool {
+        self.attr.user_writeable
+    }
+
+    fn is_user_executable(&self) -> bool {
+        self.attr.user_executable
+    }
+
+    fn is_kernel_readable(&self) -> bool {
+        self.attr.kernel_readable
+    }
+
+    fn is_kernel_writeable(&self) -> bool {
+        self.attr.kernel_writeable
+    }
+
+    fn is_kernel_executable(&self) -> bool {
+        self.attr.kernel_executable
+    }
+}
+
+// The memory type as far as it can be determined from the page tables alone.
+pub trait GenericPageMemoryType {
+    fn get_memory_type(&self) -> Option<String>;
+}
+
+impl GenericPageMemoryType for x86::X86PageRange {
+    // The PAT MSR is not known, thus only the PAT entry index is reported.
+    fn get_memory_type(&self) -> Option<String> {
+        let attr = &self.attributes;
+        let index = (attr.pat as u8) << 2 | (attr.pcd as u8) << 1 | attr.pwt as u8;
+        Some(format!("PAT{}", index))
+    }
+}
+
+impl GenericPageMemoryType for arm::ArmPageRange {
+    fn get_memory_type(&self) -> Option<String> {
+        self.attr
+            .get_memory_type()
+            .or_else(|| Some(format!("AttrIndx{}", self.attr.attr_index)))
+    }
+}
+
+impl GenericPageMemoryType for riscv::RiscvPageRange {
+    fn get_memory_type(&self) -> Option<String> {
+        None
+    }
+}
+
+impl GenericPageMemoryType for ept::EptPageRange {
+    fn get_memory_type(&self) -> Option<String> {
+        Some(String::from(self.attr.get_memory_type_str()))
+    }
+}
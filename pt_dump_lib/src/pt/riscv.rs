@@ -0,0 +1,211 @@
+use crate::memory::memory::MemoryView;
+use crate::pt::common::{Error, PhysRange};
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum RiscvMode {
+    Sv39,
+    Sv48,
+    Sv57,
+}
+
+impl RiscvMode {
+    // MODE is stored in satp[63:60]. Bare (0) is not a paging mode.
+    pub fn from_satp(satp: u64) -> Option<Self> {
+        match satp >> 60 {
+            8 => Some(RiscvMode::Sv39),
+            9 => Some(RiscvMode::Sv48),
+            10 => Some(RiscvMode::Sv57),
+            _ => None,
+        }
+    }
+
+    fn get_num_levels(self) -> u8 {
+        match self {
+            RiscvMode::Sv39 => 3,
+            RiscvMode::Sv48 => 4,
+            RiscvMode::Sv57 => 5,
+        }
+    }
+
+    fn get_va_bits(self) -> u8 {
+        PAGE_SHIFT + BITS_PER_LEVEL * self.get_num_levels()
+    }
+}
+
+const PAGE_SHIFT: u8 = 12;
+const BITS_PER_LEVEL: u8 = 9;
+const PAGE_SIZE: usize = 1 << PAGE_SHIFT;
+const ENTRY_SIZE: usize = 8;
+
+pub fn satp_to_root_ppn(satp: u64) -> u64 {
+    satp & ((1u64 << 44) - 1)
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct RiscvPageAttributes {
+    pub readable: bool,
+    pub writeable: bool,
+    pub executable: bool,
+    pub user: bool,
+    pub global: bool,
+    pub accessed: bool,
+    pub dirty: bool,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct RiscvPageRange {
+    pub va: u64,
+    pub extent: u64,
+    pub phys_ranges: Vec<PhysRange>,
+    pub attr: RiscvPageAttributes,
+}
+
+impl RiscvPageRange {
+    pub fn new(va: u64, extent: u64, attr: RiscvPageAttributes, ranges: Vec<PhysRange>) -> Self {
+        Self {
+            va,
+            extent,
+            phys_ranges: ranges,
+            attr,
+        }
+    }
+
+    pub fn get_attributes(&self) -> &RiscvPageAttributes {
+        &self.attr
+    }
+
+    fn is_extendable_by(&self, next_va: u64, next_attr: &RiscvPageAttributes) -> bool {
+        (self.va + self.extent) == next_va
+            && self.attr.readable == next_attr.readable
+            && self.attr.writeable == next_attr.writeable
+            && self.attr.executable == next_attr.executable
+            && self.attr.user == next_attr.user
+    }
+
+    fn extend_by(&mut self, next_extent: u64, next_phys: u64) {
+        self.extent += next_extent;
+        let last = self.phys_ranges.last_mut().unwrap();
+        if last.phys_base + last.phys_extent == next_phys {
+            last.phys_extent += next_extent;
+        } else {
+            // Every leaf is kept, even if its frame is already mapped, so that the physical
+            // ranges describe the virtual range in order.
+            self.phys_ranges
+                .push(PhysRange::new(next_phys, next_extent))
+        }
+    }
+}
+
+struct TablePointerEntry {
+    va: u64,
+    base_address: u64,
+    global: bool,
+    level: u8, // Counts down to 0, which is the 4K level.
+}
+
+fn canonicalize_va(mode: RiscvMode, va: u64) -> u64 {
+    let va_bits = mode.get_va_bits();
+    if (va >> (va_bits - 1)) & 1 == 1 {
+        va | !((1u64 << va_bits) - 1)
+    } else {
+        va
+    }
+}
+
+fn parse_table_riscv(
+    mode: RiscvMode,
+    memory: &mut dyn MemoryView,
+    table: &TablePointerEntry,
+    pages: &mut Vec<RiscvPageRange>,
+) -> Result<(), Error> {
+    let block = memory.read_block(table.base_address as usize, PAGE_SIZE)?;
+    let bit_start = PAGE_SHIFT + BITS_PER_LEVEL * table.level;
+    let block_size = 1u64 << bit_start;
+
+    for index in 0..PAGE_SIZE / ENTRY_SIZE {
+        let raw_entry = u64::from_le_bytes(
+            block[index * ENTRY_SIZE..(index + 1) * ENTRY_SIZE]
+                .try_into()
+                .unwrap(),
+        );
+        let has_bit = |bit_loc: u8| ((raw_entry >> bit_loc) & 1_u64) == 1_u64;
+        if !has_bit(0) {
+            continue;
+        }
+        let readable = has_bit(1);
+        let writeable = has_bit(2);
+        let executable = has_bit(3);
+        if writeable && !readable {
+            // Reserved encoding, the hardware raises a page fault.
+            continue;
+        }
+
+        let ppn = (raw_entry >> 10) & ((1u64 << 44) - 1);
+        let address = ppn << PAGE_SHIFT;
+        let va = table.va | ((index as u64) << bit_start);
+        let global = has_bit(5) || table.global;
+
+        if readable || executable {
+            // Superpages must be aligned to their size, otherwise the mapping is misaligned and faults.
+            if address & (block_size - 1) != 0 {
+                continue;
+            }
+            let attr = RiscvPageAttributes {
+                readable,
+                writeable,
+                executable,
+                user: has_bit(4),
+                global,
+                accessed: has_bit(6),
+                dirty: has_bit(7),
+            };
+            let canonical_va = canonicalize_va(mode, va);
+            if let Some(previous_page) = pages.last_mut() {
+                if previous_page.is_extendable_by(canonical_va, &attr) {
+                    previous_page.extend_by(block_size, address);
+                    continue;
+                }
+            }
+            pages.push(RiscvPageRange::new(
+                canonical_va,
+                block_size,
+                attr,
+                vec![PhysRange::new(address, block_size)],
+            ));
+        } else if table.level > 0 {
+            let next_table = TablePointerEntry {
+                va,
+                base_address: address,
+                global,
+                level: table.level - 1,
+            };
+            // Intentionally ignore errors from tables which cannot be read.
+            let _ = parse_table_riscv(mode, memory, &next_table, pages);
+        }
+    }
+    Ok(())
+}
+
+pub fn collect_pages(
+    mode: RiscvMode,
+    memory: &mut dyn MemoryView,
+    root_ppn: u64,
+) -> Result<Vec<RiscvPageRange>, Error> {
+    let root = TablePointerEntry {
+        va: 0,
+        base_address: root_ppn << PAGE_SHIFT,
+        global: false,
+        level: mode.get_num_levels() - 1,
+    };
+    let mut pages = Vec::new();
+    parse_table_riscv(mode, memory, &root, &mut pages)?;
+    Ok(pages)
+}
+
+pub fn collect_pages_from_satp(
+    memory: &mut dyn MemoryView,
+    satp: u64,
+) -> Result<Vec<RiscvPageRange>, Error> {
+    let mode = RiscvMode::from_satp(satp).ok_or(Error::UnsupportedTranslationMode)?;
+    collect_pages(mode, memory, satp_to_root_ppn(satp))
+}
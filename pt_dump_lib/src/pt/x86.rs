@@ -1,8 +1,11 @@
 use crate::memory::memory;
-use crate::pt::common::{Error, PhysRange};
+use crate::pt::common::{Error, PhysRange, WalkFaultReason};
+use rayon::prelude::*;
 
+#[derive(Clone)]
 struct X86Context {
     flavour: X86Flavour,
+    pml5e_range: Option<(u8, u8)>, // Only valid for x86-64 with LA57
     pml4e_range: Option<(u8, u8)>, // This is not valid for x86-32
     pdpe_range: Option<(u8, u8)>,  // Valid for x86-32 only if PAE is used. For x86-32-pae, 30:31
     pde_range: (u8, u8),           // 21:29 (PAE) or 22:31
@@ -11,10 +14,13 @@ struct X86Context {
     entry_size: usize,
     pse: bool, // set to true if in PAE mode or if flavour is x86_64
     pae: bool,
+    la57: bool,
+    canonical_addresses: bool, // Not the case for nested page tables
 }
 
 #[derive(Copy, Clone, PartialEq, Debug)]
-enum LevelType {
+pub enum LevelType {
+    PML5,
     PML4,
     PDP,
     PD,
@@ -23,6 +29,7 @@ enum LevelType {
 
 fn get_next_level_type(lvl: LevelType) -> LevelType {
     match lvl {
+        LevelType::PML5 => LevelType::PML4,
         LevelType::PML4 => LevelType::PDP,
         LevelType::PDP => LevelType::PD,
         LevelType::PD => LevelType::PT,
@@ -32,10 +39,11 @@ fn get_next_level_type(lvl: LevelType) -> LevelType {
 
 fn level_type_to_index(lvl: LevelType) -> usize {
     match lvl {
-        LevelType::PML4 => 0,
-        LevelType::PDP => 1,
-        LevelType::PD => 2,
-        LevelType::PT => 3,
+        LevelType::PML5 => 0,
+        LevelType::PML4 => 1,
+        LevelType::PDP => 2,
+        LevelType::PD => 3,
+        LevelType::PT => 4,
     }
 }
 
@@ -113,19 +121,19 @@ impl X86PageRange {
             && self.attributes.writeable == next_attributes.writeable
             && self.attributes.user == next_attributes.user
             && self.attributes.nx == next_attributes.nx
+            && self.attributes.pwt == next_attributes.pwt
+            && self.attributes.pcd == next_attributes.pcd
+            && self.attributes.pat == next_attributes.pat
     }
 
     fn extend_by(&mut self, next_extent: u64, next_phys: u64) {
         self.extent += next_extent;
-        let mut last = self.phys_ranges.last_mut().unwrap();
+        let last = self.phys_ranges.last_mut().unwrap();
         if last.phys_base + last.phys_extent == next_phys {
             last.phys_extent += next_extent;
-        } else if last.phys_base <= next_phys
-            && (next_phys + next_extent) <= (last.phys_base + last.phys_extent)
-        {
-            // Don't do anything since the range is already included and the range is considered semantically the same.
-            return;
         } else {
+            // Every leaf is kept, even if its frame is already mapped, so that the physical
+            // ranges describe the virtual range in order.
             self.phys_ranges
                 .push(PhysRange::new(next_phys, next_extent))
         }
@@ -160,8 +168,9 @@ fn parse_entry<'h>(
     let dirty = has_bit(6);
     let ps = if x86_context.pse { has_bit(7) } else { false };
     let global = has_bit(8);
+    // PAT is bit 7 of a PTE, where it doesn't collide with PS.
     let pat = if current_level == LevelType::PT {
-        false
+        has_bit(7)
     } else {
         has_bit(12)
     };
@@ -174,6 +183,7 @@ fn parse_entry<'h>(
     let end_entry = ps || (current_level == LevelType::PT);
     let address_mask = if end_entry {
         match current_level {
+            LevelType::PML5 => mask_range((x86_context.pml5e_range.unwrap().0, 51)),
             LevelType::PML4 => mask_range((x86_context.pml4e_range.unwrap().0, 51)),
             LevelType::PDP => mask_range((x86_context.pdpe_range.unwrap().0, 51)),
             LevelType::PD => mask_range((x86_context.pde_range.0, 51)),
@@ -187,9 +197,10 @@ fn parse_entry<'h>(
     let address = raw_entry & address_mask;
 
     let result: TableEntry = if end_entry {
-        let top_address_bit = (va_contribution >> 47) & 1;
-        let canonical_va = if top_address_bit != 0 {
-            let canonical_va_mask = 0xFFFF000000000000_u64;
+        let top_address_bit_index = if x86_context.la57 { 56 } else { 47 };
+        let top_address_bit = (va_contribution >> top_address_bit_index) & 1;
+        let canonical_va = if top_address_bit != 0 && x86_context.canonical_addresses {
+            let canonical_va_mask = !((1_u64 << (top_address_bit_index + 1)) - 1);
             va_contribution | canonical_va_mask
         } else {
             va_contribution
@@ -230,120 +241,200 @@ fn parse_entry<'h>(
     Ok(Some(result))
 }
 
-fn collect_entries_recursive<'a>(
-    memory: &mut dyn memory::MemoryView,
-    x86_context: &X86Context,
-    current_page_table: &TablePointerEntry,
-    mut scratch_memory: &mut Vec<Vec<u8>>,
+fn get_root_table(x86_context: &X86Context, pa: u64) -> TablePointerEntry {
+    TablePointerEntry {
+        table_address: pa,
+        level: get_root_level(x86_context),
+        remaining_bits: if x86_context.la57 {
+            57
+        } else if x86_context.flavour == X86Flavour::X64 {
+            48
+        } else {
+            32
+        },
+        va: 0,
+    }
+}
+
+fn get_root_block_size(x86_context: &X86Context) -> usize {
+    // The PAE PDPT only has 4 entries.
+    if x86_context.flavour == X86Flavour::X86 && x86_context.pae {
+        4 * 8
+    } else {
+        x86_context.page_size
+    }
+}
+
+struct WalkFrame {
+    table: TablePointerEntry,
     block_size: usize,
-    pages: &mut Vec<X86PageRange>,
-) {
-    let bits_contribution = match (
-        x86_context.flavour,
-        current_page_table.level,
-        x86_context.entry_size,
-    ) {
-        (_, _, 4) => 10,
-        (X86Flavour::X86, LevelType::PDP, 8) => 2,
-        (_, _, 8) => 9,
-        _ => unreachable!(),
-    };
-    let current_block = scratch_memory[level_type_to_index(current_page_table.level)].as_ptr();
+    next_index: usize,
+}
 
-    for index in 0..block_size / x86_context.entry_size {
-        let raw_entry: u64 = match x86_context.entry_size {
-            4 => {
-                (unsafe { *(current_block.add(index * x86_context.entry_size) as *const u32) })
-                    as u64
-            }
-            8 => unsafe { *(current_block.add(index * x86_context.entry_size) as *const u64) },
-            _ => unreachable!(),
+// Walks the page table in DFS order and yields the coalesced page ranges one at a time.
+// Tables below the root which can't be read are yielded as errors in VA order.
+pub struct X86PageWalker<'a> {
+    memory: &'a mut dyn memory::MemoryView,
+    x86_context: X86Context,
+    // The algorithm perform DFS, and thus at most Five blocks are used at any point.
+    scratch_memory: Vec<Vec<u8>>,
+    stack: Vec<WalkFrame>,
+    pending_page: Option<X86PageRange>, // The range which may still be extended
+    pending_error: Option<Error>,       // Yielded after the pending range
+}
+
+impl<'a> X86PageWalker<'a> {
+    fn new(
+        memory: &'a mut dyn memory::MemoryView,
+        pa: u64,
+        x86_context: X86Context,
+    ) -> Result<Self, Error> {
+        let scratch_memory = vec![vec![0u8; x86_context.page_size]; 5];
+        let mut walker = Self {
+            memory,
+            x86_context,
+            scratch_memory,
+            stack: vec![],
+            pending_page: None,
+            pending_error: None,
         };
-        let present = (raw_entry & 0x1) == 0x1;
-        if !present {
-            // Early optimization for not present
-            continue;
+        let root = get_root_table(&walker.x86_context, pa);
+        let block_size = get_root_block_size(&walker.x86_context);
+        walker.read_table(&root, block_size)?;
+        Ok(walker)
+    }
+
+    // Walks only the subtree of a single entry of the already read root table.
+    fn new_subtree(
+        memory: &'a mut dyn memory::MemoryView,
+        pa: u64,
+        x86_context: X86Context,
+        root_block: &[u8],
+        index: usize,
+    ) -> Self {
+        let root = get_root_table(&x86_context, pa);
+        let mut scratch_memory = vec![vec![0u8; x86_context.page_size]; 5];
+        scratch_memory[level_type_to_index(root.level)][..root_block.len()]
+            .copy_from_slice(root_block);
+        let frame = WalkFrame {
+            table: root,
+            block_size: (index + 1) * x86_context.entry_size,
+            next_index: index,
+        };
+        Self {
+            memory,
+            x86_context,
+            scratch_memory,
+            stack: vec![frame],
+            pending_page: None,
+            pending_error: None,
         }
-        let remaining_bits = current_page_table.remaining_bits - bits_contribution;
-        let va_contribution = (index as u64) << remaining_bits;
-        let block_size = 1_u64 << remaining_bits;
-        let entry = parse_entry(
-            &x86_context,
-            current_page_table.level,
-            raw_entry,
-            current_page_table.va | va_contribution,
-            remaining_bits,
+    }
+
+    fn read_table(&mut self, table: &TablePointerEntry, block_size: usize) -> Result<(), Error> {
+        self.memory.read_block_inplace(
+            table.table_address as usize,
             block_size,
-            &mut pages.last_mut(),
-        )
-        .unwrap();
-        match entry {
-            Some(TableEntry::TablePointerEntry(table)) => {
-                // Intentionally ignore errors
-                let index = level_type_to_index(table.level);
-                let result = memory.read_block_inplace(
-                    table.table_address as usize,
-                    x86_context.page_size,
-                    &mut scratch_memory[index][..],
-                );
-                if result.is_ok() {
-                    collect_entries_recursive(
-                        memory,
-                        x86_context,
-                        &table,
-                        &mut scratch_memory,
-                        x86_context.page_size,
-                        pages,
-                    );
+            &mut self.scratch_memory[level_type_to_index(table.level)][..],
+        )?;
+        self.stack.push(WalkFrame {
+            table: *table,
+            block_size,
+            next_index: 0,
+        });
+        Ok(())
+    }
+
+    // The pending range precedes the failed table, thus it is yielded first.
+    fn fail(&mut self, err: Error) -> Option<Result<X86PageRange, Error>> {
+        match self.pending_page.take() {
+            Some(previous_page) => {
+                self.pending_error = Some(err);
+                Some(Ok(previous_page))
+            }
+            None => Some(Err(err)),
+        }
+    }
+
+    // Returns the next range which can no longer be extended.
+    fn next_page(&mut self) -> Option<Result<X86PageRange, Error>> {
+        if let Some(err) = self.pending_error.take() {
+            return Some(Err(err));
+        }
+        while let Some(frame) = self.stack.last_mut() {
+            let x86_context = &self.x86_context;
+            if frame.next_index * x86_context.entry_size >= frame.block_size {
+                self.stack.pop();
+                continue;
+            }
+            let index = frame.next_index;
+            frame.next_index += 1;
+            let current_page_table = frame.table;
+
+            let current_block = &self.scratch_memory[level_type_to_index(current_page_table.level)];
+            let offset = index * x86_context.entry_size;
+            let raw_entry: u64 = match x86_context.entry_size {
+                4 => {
+                    u32::from_le_bytes(current_block[offset..offset + 4].try_into().unwrap()) as u64
                 }
+                8 => u64::from_le_bytes(current_block[offset..offset + 8].try_into().unwrap()),
+                _ => unreachable!(),
+            };
+            let present = (raw_entry & 0x1) == 0x1;
+            if !present {
+                // Early optimization for not present
+                continue;
             }
-            Some(TableEntry::X86PageRange(mapping)) => {
-                pages.push(mapping);
+            let bits_contribution = match (
+                x86_context.flavour,
+                current_page_table.level,
+                x86_context.entry_size,
+            ) {
+                (_, _, 4) => 10,
+                (X86Flavour::X86, LevelType::PDP, 8) => 2,
+                (_, _, 8) => 9,
+                _ => unreachable!(),
+            };
+            let remaining_bits = current_page_table.remaining_bits - bits_contribution;
+            let va_contribution = (index as u64) << remaining_bits;
+            let block_size = 1_u64 << remaining_bits;
+            let entry = match parse_entry(
+                x86_context,
+                current_page_table.level,
+                raw_entry,
+                current_page_table.va | va_contribution,
+                remaining_bits,
+                block_size,
+                &mut self.pending_page.as_mut(),
+            ) {
+                Ok(entry) => entry,
+                Err(err) => return self.fail(err),
+            };
+            match entry {
+                Some(TableEntry::TablePointerEntry(table)) => {
+                    let page_size = x86_context.page_size;
+                    if let Err(err) = self.read_table(&table, page_size) {
+                        return self.fail(err);
+                    }
+                }
+                Some(TableEntry::X86PageRange(mapping)) => {
+                    if let Some(previous_page) = self.pending_page.replace(mapping) {
+                        return Some(Ok(previous_page));
+                    }
+                }
+                None => continue,
             }
-            None => continue,
         }
+        self.pending_page.take().map(Ok)
     }
 }
 
-fn collect_pages_common(
-    memory: &mut dyn memory::MemoryView,
-    flavour: X86Flavour,
-    root: &TablePointerEntry,
-    x86_context: &X86Context,
-    mut pages: &mut Vec<X86PageRange>,
-) -> Result<(), Error> {
-    // The algorithm perform DFS, and thus at most Five blocks are used at any point.
-    let mut scratch_memory = vec![vec![0u8; x86_context.page_size]; 5];
-    if flavour == X86Flavour::X86 && x86_context.pae {
-        memory.read_block_inplace(
-            root.table_address as usize,
-            4 * 8,
-            &mut scratch_memory[level_type_to_index(root.level)][..],
-        )?;
-        collect_entries_recursive(
-            memory,
-            &x86_context,
-            root,
-            &mut scratch_memory,
-            4 * 8,
-            &mut pages,
-        );
-    } else {
-        memory.read_block_inplace(
-            root.table_address as usize,
-            x86_context.page_size,
-            &mut scratch_memory[level_type_to_index(root.level)][..],
-        )?;
-        collect_entries_recursive(
-            memory,
-            &x86_context,
-            root,
-            &mut scratch_memory,
-            x86_context.page_size,
-            &mut pages,
-        );
+impl<'a> Iterator for X86PageWalker<'a> {
+    type Item = Result<X86PageRange, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_page()
     }
-    Ok(())
 }
 
 #[derive(PartialEq, Clone, Copy)]
@@ -353,6 +444,7 @@ pub enum X86Flavour {
 }
 
 struct LevelRanges {
+    pml5e_range: Option<(u8, u8)>,
     pml4e_range: Option<(u8, u8)>,
     pdpe_range: Option<(u8, u8)>,
     pde_range: (u8, u8),
@@ -363,6 +455,7 @@ fn gen_level_ranges(flavour: X86Flavour, pae: bool) -> LevelRanges {
     if flavour == X86Flavour::X86 {
         if pae {
             LevelRanges {
+                pml5e_range: None,
                 pml4e_range: None,
                 pdpe_range: Some((30, 31)),
                 pde_range: (21, 29),
@@ -370,6 +463,7 @@ fn gen_level_ranges(flavour: X86Flavour, pae: bool) -> LevelRanges {
             }
         } else {
             LevelRanges {
+                pml5e_range: None,
                 pml4e_range: None,
                 pdpe_range: None,
                 pde_range: (22, 31),
@@ -378,6 +472,7 @@ fn gen_level_ranges(flavour: X86Flavour, pae: bool) -> LevelRanges {
         }
     } else if flavour == X86Flavour::X64 {
         LevelRanges {
+            pml5e_range: Some((48, 56)),
             pml4e_range: Some((39, 47)),
             pdpe_range: Some((30, 38)),
             pde_range: (21, 29),
@@ -388,14 +483,7 @@ fn gen_level_ranges(flavour: X86Flavour, pae: bool) -> LevelRanges {
     }
 }
 
-pub fn collect_pages(
-    flavour: X86Flavour,
-    memory: &mut dyn memory::MemoryView,
-    pa: u64,
-    pse: bool,
-    pae: bool,
-) -> Result<Vec<X86PageRange>, Error> {
-    // Construct the x86 context for parsing
+fn create_x86_context(flavour: X86Flavour, pse: bool, pae: bool, la57: bool) -> X86Context {
     let (num_entries, entry_size) = if flavour == X86Flavour::X86 {
         if pae {
             (512usize, 8usize)
@@ -408,8 +496,9 @@ pub fn collect_pages(
         unreachable!();
     };
     let ranges = gen_level_ranges(flavour, pae);
-    let x86_context = X86Context {
+    X86Context {
         flavour: flavour,
+        pml5e_range: ranges.pml5e_range,
         pml4e_range: ranges.pml4e_range,
         pdpe_range: ranges.pdpe_range,
         pde_range: ranges.pde_range,
@@ -422,21 +511,296 @@ pub fn collect_pages(
             pse
         },
         pae: pae,
+        la57: la57 && flavour == X86Flavour::X64,
+        canonical_addresses: true,
+    }
+}
+
+fn collect_pages_with_context(
+    memory: &mut dyn memory::MemoryView,
+    pa: u64,
+    x86_context: X86Context,
+) -> Result<Vec<X86PageRange>, Error> {
+    // Intentionally skip tables below the root which cannot be read.
+    Ok(X86PageWalker::new(memory, pa, x86_context)?
+        .filter_map(Result::ok)
+        .collect())
+}
+
+// Unlike collect_pages, the ranges are produced lazily while walking the page table. Only
+// the root table is read up front.
+pub fn walk<'a>(
+    flavour: X86Flavour,
+    memory: &'a mut dyn memory::MemoryView,
+    pa: u64,
+    pse: bool,
+    pae: bool,
+    la57: bool,
+) -> Result<X86PageWalker<'a>, Error> {
+    X86PageWalker::new(memory, pa, create_x86_context(flavour, pse, pae, la57))
+}
+
+pub fn collect_pages(
+    flavour: X86Flavour,
+    memory: &mut dyn memory::MemoryView,
+    pa: u64,
+    pse: bool,
+    pae: bool,
+    la57: bool,
+) -> Result<Vec<X86PageRange>, Error> {
+    let x86_context = create_x86_context(flavour, pse, pae, la57);
+    collect_pages_with_context(memory, pa, x86_context)
+}
+
+// Walks the subtrees of the root table entries in parallel. Each thread reads through its own
+// clone of the memory view. The results are identical to collect_pages.
+pub fn collect_pages_parallel<M: memory::MemoryView + Clone>(
+    flavour: X86Flavour,
+    memory: &M,
+    pa: u64,
+    pse: bool,
+    pae: bool,
+    la57: bool,
+) -> Result<Vec<X86PageRange>, Error> {
+    let x86_context = create_x86_context(flavour, pse, pae, la57);
+    let root_block = memory
+        .clone()
+        .read_block(pa as usize, get_root_block_size(&x86_context))?;
+    let present_indices: Vec<usize> = (0..root_block.len() / x86_context.entry_size)
+        .filter(|index| root_block[index * x86_context.entry_size] & 1 == 1)
+        .collect();
+
+    let subtrees: Vec<Vec<X86PageRange>> = present_indices
+        .par_iter()
+        .map_with(memory.clone(), |memory, &index| {
+            let walker =
+                X86PageWalker::new_subtree(memory, pa, x86_context.clone(), &root_block, index);
+            // Intentionally skip tables below the root which cannot be read.
+            walker.filter_map(Result::ok).collect()
+        })
+        .collect();
+
+    // Coalesce the ranges at the subtree boundaries. The physical ranges hold every leaf in
+    // VA order, so extending by them one by one is the same as extending leaf by leaf.
+    let mut pages: Vec<X86PageRange> = vec![];
+    for subtree_pages in subtrees {
+        let mut subtree_pages = subtree_pages.into_iter().peekable();
+        if let Some(previous_page) = pages.last_mut() {
+            if let Some(next_page) = subtree_pages.next_if(|next_page| {
+                previous_page.is_extendable_by(next_page.va, &next_page.attributes)
+            }) {
+                let extent = previous_page.extent + next_page.extent;
+                for phys_range in next_page.phys_ranges {
+                    previous_page.extend_by(phys_range.phys_extent, phys_range.phys_base);
+                }
+                debug_assert_eq!(extent, previous_page.extent);
+            }
+        }
+        pages.extend(subtree_pages);
+    }
+    Ok(pages)
+}
+
+// AMD NPT uses the long mode format, but the walked addresses are guest physical
+// and thus not sign-extended. The resulting "virtual" addresses are GPAs.
+pub fn collect_npt_pages(
+    memory: &mut dyn memory::MemoryView,
+    ncr3: u64,
+    la57: bool,
+) -> Result<Vec<X86PageRange>, Error> {
+    let mut x86_context = create_x86_context(X86Flavour::X64, true, true, la57);
+    x86_context.canonical_addresses = false;
+    collect_pages_with_context(memory, ncr3, x86_context)
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum X86WalkEntry {
+    NotPresent,
+    Table { next_table_pa: u64 },
+    Leaf { page_pa: u64, page_size: u64 },
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct X86WalkStep {
+    pub level: LevelType,
+    pub index: usize,
+    pub table_pa: u64,
+    pub raw_entry: u64,
+    pub attributes: PageAttributes, // Bits of this entry only
+    pub entry: X86WalkEntry,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct X86TranslatedAddress {
+    pub pa: u64,
+    pub page_size: u64,
+    pub attributes: PageAttributes, // Effective permissions accumulated over all levels
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct X86WalkFault {
+    pub level: LevelType,
+    pub reason: WalkFaultReason,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct X86Translation {
+    pub va: u64,
+    pub steps: Vec<X86WalkStep>,
+    pub result: Result<X86TranslatedAddress, X86WalkFault>,
+}
+
+fn get_level_bit_range(x86_context: &X86Context, level: LevelType) -> (u8, u8) {
+    match level {
+        LevelType::PML5 => x86_context.pml5e_range.unwrap(),
+        LevelType::PML4 => x86_context.pml4e_range.unwrap(),
+        LevelType::PDP => x86_context.pdpe_range.unwrap(),
+        LevelType::PD => x86_context.pde_range,
+        LevelType::PT => x86_context.pte_range,
+    }
+}
+
+fn get_root_level(x86_context: &X86Context) -> LevelType {
+    if x86_context.la57 {
+        LevelType::PML5
+    } else if x86_context.flavour == X86Flavour::X64 {
+        LevelType::PML4
+    } else if x86_context.pae {
+        LevelType::PDP
+    } else {
+        LevelType::PD
+    }
+}
+
+fn translate_with_context(
+    memory: &mut dyn memory::MemoryView,
+    pa: u64,
+    x86_context: &X86Context,
+    va: u64,
+) -> X86Translation {
+    let mut steps = vec![];
+    let mut level = get_root_level(x86_context);
+    let fault = |level: LevelType, reason: WalkFaultReason| Err(X86WalkFault { level, reason });
+
+    let va_bits = match x86_context.flavour {
+        X86Flavour::X64 if x86_context.la57 => 57,
+        X86Flavour::X64 => 48,
+        X86Flavour::X86 => 32,
+    };
+    let upper_bits = va >> (va_bits - 1);
+    let in_range = if x86_context.flavour == X86Flavour::X64 && x86_context.canonical_addresses {
+        upper_bits == 0 || upper_bits == u64::MAX >> (va_bits - 1)
+    } else {
+        va >> va_bits == 0
     };
+    if !in_range {
+        return X86Translation {
+            va,
+            steps,
+            result: fault(level, WalkFaultReason::AddressOutOfRange),
+        };
+    }
 
-    let mut pages = Vec::new();
-    let root = TablePointerEntry {
-        table_address: pa,
-        level: if flavour == X86Flavour::X64 {
-            LevelType::PML4
-        } else if flavour == X86Flavour::X86 && pae {
-            LevelType::PDP
+    let mut table_pa = pa;
+    let mut writeable = true;
+    let mut user = true;
+    let mut nx = false;
+    let result = loop {
+        let (bit_start, bit_end) = get_level_bit_range(x86_context, level);
+        let index = ((va >> bit_start) & ((1u64 << (bit_end - bit_start + 1)) - 1)) as usize;
+        let entry_size = x86_context.entry_size;
+        let raw_entry = match memory.read_block(table_pa as usize + index * entry_size, entry_size)
+        {
+            Ok(block) => match entry_size {
+                4 => u32::from_le_bytes(block[..].try_into().unwrap()) as u64,
+                _ => u64::from_le_bytes(block[..].try_into().unwrap()),
+            },
+            Err(_) => break fault(level, WalkFaultReason::TableNotReadable),
+        };
+        let has_bit = |bit_loc: u8| ((raw_entry >> bit_loc) & 1_u64) == 1_u64;
+        // The PAE PDPTEs only hold the present bit and the cache attributes.
+        let pae_pdpte = x86_context.flavour == X86Flavour::X86 && level == LevelType::PDP;
+        let attributes = PageAttributes {
+            accessed: has_bit(5),
+            dirty: has_bit(6),
+            writeable: has_bit(1) || pae_pdpte,
+            user: has_bit(2) || pae_pdpte,
+            pwt: has_bit(3),
+            pcd: has_bit(4),
+            pat: if level == LevelType::PT {
+                has_bit(7)
+            } else {
+                has_bit(12)
+            },
+            global: has_bit(8),
+            nx: has_bit(63),
+        };
+        let large_page = has_bit(7)
+            && ((level == LevelType::PD && x86_context.pse)
+                || (level == LevelType::PDP && x86_context.flavour == X86Flavour::X64));
+        let present = has_bit(0);
+        let leaf = level == LevelType::PT || large_page;
+        let mask_from = |bit: u8| ((1_u64 << 52) - 1) & !((1_u64 << bit) - 1);
+        let entry = if !present {
+            X86WalkEntry::NotPresent
+        } else if leaf {
+            X86WalkEntry::Leaf {
+                page_pa: raw_entry & mask_from(bit_start),
+                page_size: 1u64 << bit_start,
+            }
         } else {
-            LevelType::PD
-        },
-        remaining_bits: if flavour == X86Flavour::X64 { 48 } else { 32 },
-        va: 0,
+            X86WalkEntry::Table {
+                next_table_pa: raw_entry & mask_from(12),
+            }
+        };
+        steps.push(X86WalkStep {
+            level,
+            index,
+            table_pa,
+            raw_entry,
+            attributes: attributes.clone(),
+            entry: entry.clone(),
+        });
+
+        writeable &= attributes.writeable;
+        user &= attributes.user;
+        nx |= attributes.nx;
+        match entry {
+            X86WalkEntry::NotPresent => break fault(level, WalkFaultReason::NotPresent),
+            X86WalkEntry::Table { next_table_pa } => {
+                table_pa = next_table_pa;
+                level = get_next_level_type(level);
+            }
+            X86WalkEntry::Leaf { page_pa, page_size } => {
+                break Ok(X86TranslatedAddress {
+                    pa: page_pa | (va & (page_size - 1)),
+                    page_size,
+                    attributes: PageAttributes {
+                        writeable,
+                        user,
+                        nx,
+                        ..attributes
+                    },
+                });
+            }
+        }
     };
-    collect_pages_common(memory, flavour, &root, &x86_context, &mut pages)?;
-    Ok(pages)
+    X86Translation { va, steps, result }
+}
+
+// Walks only the entries which are used to translate the given virtual address. Faults of
+// the walk are part of the translation, as for arm::translate.
+pub fn translate(
+    flavour: X86Flavour,
+    memory: &mut dyn memory::MemoryView,
+    pa: u64,
+    pse: bool,
+    pae: bool,
+    la57: bool,
+    va: u64,
+) -> Result<X86Translation, Error> {
+    let x86_context = create_x86_context(flavour, pse, pae, la57);
+    // A root table which can't be read is an error, not a fault of the translated address.
+    memory.read_block(pa as usize, get_root_block_size(&x86_context))?;
+    Ok(translate_with_context(memory, pa, &x86_context, va))
 }
@@ -1 +1,5 @@
+pub mod address_spaces;
 pub mod bytes_search;
+pub mod kaslr;
+pub mod reverse_mapping;
+pub mod root_scan;
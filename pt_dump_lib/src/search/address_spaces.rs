@@ -0,0 +1,148 @@
+use crate::memory::memory::MemoryView;
+use crate::pt::common::Error;
+use crate::pt::x86::{self, X86Flavour};
+use crate::search::root_scan::RootCandidate;
+use std::collections::HashMap;
+
+const PAGE_SIZE: usize = 0x1000;
+const NUM_ENTRIES: usize = 512;
+const USER_SPACE_END: u64 = 0x0000_8000_0000_0000;
+
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct UserSpaceSummary {
+    pub ranges: usize,
+    pub mapped_bytes: u64,
+    pub writeable_bytes: u64,
+    pub executable_bytes: u64,
+    pub lowest_va: Option<u64>,
+    pub highest_va: Option<u64>, // Last mapped byte
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct ProcessAddressSpace {
+    pub root_pa: u64,
+    pub user_space: UserSpaceSummary,
+}
+
+// Roots sharing the same kernel half, i.e. the processes of one kernel.
+#[derive(Clone, PartialEq, Debug)]
+pub struct KernelAddressSpace {
+    pub kernel_entries: Vec<u64>, // PML4 entries 256-511 of the first process
+    pub processes: Vec<ProcessAddressSpace>,
+}
+
+// Hides the kernel half of a root table, so that only the user half is walked.
+struct UserHalfMemoryView<'a> {
+    memory: &'a mut dyn MemoryView,
+    root_pa: usize,
+    root_page: Vec<u8>,
+}
+
+impl<'a> MemoryView for UserHalfMemoryView<'a> {
+    fn read_block(&mut self, offset: usize, block_size: usize) -> Result<Vec<u8>, Error> {
+        let mut block = vec![0u8; block_size];
+        self.read_block_inplace(offset, block_size, &mut block[..])?;
+        Ok(block)
+    }
+
+    fn read_block_inplace(
+        &mut self,
+        offset: usize,
+        block_size: usize,
+        block: &mut [u8],
+    ) -> Result<(), Error> {
+        self.memory.read_block_inplace(offset, block_size, block)?;
+        let start = offset.max(self.root_pa);
+        let end = (offset + block_size).min(self.root_pa + PAGE_SIZE);
+        if start < end {
+            block[start - offset..end - offset]
+                .copy_from_slice(&self.root_page[start - self.root_pa..end - self.root_pa]);
+        }
+        Ok(())
+    }
+}
+
+fn read_root_entries(memory: &mut dyn MemoryView, pa: u64) -> Result<Vec<u64>, Error> {
+    let page = memory.read_block(pa as usize, PAGE_SIZE)?;
+    Ok(page
+        .chunks_exact(8)
+        .map(|entry| u64::from_le_bytes(entry.try_into().unwrap()))
+        .collect())
+}
+
+pub fn summarize_user_space(
+    memory: &mut dyn MemoryView,
+    root_pa: u64,
+) -> Result<UserSpaceSummary, Error> {
+    let mut root_page = memory.read_block(root_pa as usize, PAGE_SIZE)?;
+    root_page[PAGE_SIZE / 2..].fill(0);
+    let mut user_half_memory = UserHalfMemoryView {
+        memory,
+        root_pa: root_pa as usize,
+        root_page,
+    };
+    let ranges = x86::collect_pages(
+        X86Flavour::X64,
+        &mut user_half_memory,
+        root_pa,
+        true,
+        true,
+        false,
+    )?;
+
+    let mut summary = UserSpaceSummary::default();
+    for range in ranges.iter().filter(|range| range.va < USER_SPACE_END) {
+        let attributes = range.get_attributes();
+        summary.ranges += 1;
+        summary.mapped_bytes += range.extent;
+        if attributes.writeable {
+            summary.writeable_bytes += range.extent;
+        }
+        if !attributes.nx {
+            summary.executable_bytes += range.extent;
+        }
+        summary.lowest_va.get_or_insert(range.va);
+        summary.highest_va = Some(range.va + (range.extent - 1));
+    }
+    Ok(summary)
+}
+
+// Groups x86-64 root candidates by their kernel half. Candidates without any kernel entries
+// or whose root table can't be read are left out. The kernels with the most processes come
+// first.
+pub fn group_into_address_spaces(
+    memory: &mut dyn MemoryView,
+    candidates: &[RootCandidate],
+) -> Vec<KernelAddressSpace> {
+    let mut kernels: Vec<KernelAddressSpace> = vec![];
+    let mut kernel_indices: HashMap<Vec<u64>, usize> = HashMap::new();
+    for candidate in candidates.iter().filter(|c| c.kernel_entries > 0) {
+        let entries = match read_root_entries(memory, candidate.pa) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+        let user_space = match summarize_user_space(memory, candidate.pa) {
+            Ok(user_space) => user_space,
+            Err(_) => continue,
+        };
+        let kernel_entries = entries[NUM_ENTRIES / 2..].to_vec();
+        // Accessed and dirty bits differ between otherwise identical kernel halves.
+        let key: Vec<u64> = kernel_entries.iter().map(|entry| entry & !0x60).collect();
+        let index = *kernel_indices.entry(key).or_insert_with(|| {
+            kernels.push(KernelAddressSpace {
+                kernel_entries,
+                processes: vec![],
+            });
+            kernels.len() - 1
+        });
+        kernels[index].processes.push(ProcessAddressSpace {
+            root_pa: candidate.pa,
+            user_space,
+        });
+    }
+    for kernel in kernels.iter_mut() {
+        kernel.processes.sort_by_key(|process| process.root_pa);
+    }
+    kernels.sort_by_key(|kernel| std::cmp::Reverse(kernel.processes.len()));
+    kernels
+}
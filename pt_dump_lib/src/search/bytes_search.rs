@@ -1,10 +1,19 @@
 use crate::memory::memory::MemoryView;
+use crate::pt::common::Error;
 use crate::pt::page_range::GenericPageRange;
+use aho_corasick::AhoCorasick;
 use memchr::memmem;
+use rayon::prelude::*;
+use regex::bytes::Regex;
+use std::ops::Range;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
 
 pub struct SearchResultOccurrence {
     pub va: u64,
+    pub length: u64,
     pub range_index: usize,
+    pub needle_index: usize, // Always 0 unless several needles are searched for
 }
 
 pub struct SearchResult {
@@ -18,11 +27,14 @@ impl SearchResult {
         }
     }
 
-    fn add_result(&mut self, range_index: usize, va_addr: u64) {
-        self.occurrences.push(SearchResultOccurrence {
-            va: va_addr,
-            range_index: range_index,
-        })
+    fn add_chunk_results(
+        &mut self,
+        occurrences: Vec<SearchResultOccurrence>,
+        max_num_occurrences: usize,
+    ) {
+        let num_added = max_num_occurrences.saturating_sub(self.occurrences.len());
+        self.occurrences
+            .extend(occurrences.into_iter().take(num_added));
     }
 
     pub fn get_results(&self) -> &Vec<SearchResultOccurrence> {
@@ -30,9 +42,484 @@ impl SearchResult {
     }
 }
 
-pub fn search_memory_generic<RangeType: GenericPageRange>(
-    needle: &[u8],
-    ranges: &Vec<RangeType>,
+pub struct PatternMatch {
+    pub range: Range<usize>,
+    pub needle_index: usize,
+}
+
+impl PatternMatch {
+    fn new(range: Range<usize>) -> Self {
+        Self {
+            range,
+            needle_index: 0,
+        }
+    }
+}
+
+// Anything which can be searched for in a block of memory.
+pub trait SearchPattern: Sync {
+    // Upper bound of the length of a match, used for the overlap between chunks.
+    fn max_match_len(&self) -> usize;
+    // The matches ordered by their start offset.
+    fn find_iter<'a>(&'a self, haystack: &'a [u8]) -> Box<dyn Iterator<Item = PatternMatch> + 'a>;
+    fn has_overlapping_matches(&self) -> bool {
+        false
+    }
+}
+
+// Non-overlapping occurrences of the needle, as memmem reports them.
+impl SearchPattern for memmem::Finder<'_> {
+    fn max_match_len(&self) -> usize {
+        self.needle().len()
+    }
+
+    fn find_iter<'a>(&'a self, haystack: &'a [u8]) -> Box<dyn Iterator<Item = PatternMatch> + 'a> {
+        let needle_len = self.needle().len();
+        Box::new(
+            memmem::Finder::find_iter(self, haystack)
+                .map(move |start| PatternMatch::new(start..start + needle_len)),
+        )
+    }
+}
+
+// A byte pattern where only the bits set in the mask of each byte have to match.
+#[derive(Clone, Debug)]
+pub struct BytePattern {
+    values: Vec<u8>,
+    masks: Vec<u8>,
+    // The longest run of fully masked bytes, which is searched for first
+    anchor: Option<(usize, memmem::Finder<'static>)>,
+}
+
+impl BytePattern {
+    pub fn new(values: &[u8], masks: &[u8]) -> Result<Self, Error> {
+        if values.is_empty() || values.len() != masks.len() {
+            return Err(Error::InvalidSearchPattern);
+        }
+        let mut longest_run = (0, 0);
+        let mut run_start = 0;
+        for (index, mask) in masks.iter().enumerate() {
+            if *mask != 0xff {
+                run_start = index + 1;
+            } else if index + 1 - run_start > longest_run.1 {
+                longest_run = (run_start, index + 1 - run_start);
+            }
+        }
+        let anchor = if longest_run.1 > 0 {
+            let anchor_bytes = &values[longest_run.0..longest_run.0 + longest_run.1];
+            Some((
+                longest_run.0,
+                memmem::Finder::new(anchor_bytes).into_owned(),
+            ))
+        } else {
+            None
+        };
+        Ok(Self {
+            values: values.iter().zip(masks).map(|(v, m)| v & m).collect(),
+            masks: masks.to_vec(),
+            anchor,
+        })
+    }
+
+    // Parses whitespace separated hex bytes, e.g. "48 8b ?? ?? 00 00 e8". A "?" stands for
+    // a wildcard nibble.
+    pub fn parse(pattern: &str) -> Result<Self, Error> {
+        let mut values = vec![];
+        let mut masks = vec![];
+        for token in pattern.split_whitespace() {
+            let nibbles: Vec<char> = token.chars().collect();
+            if nibbles.len() != 2 {
+                return Err(Error::InvalidSearchPattern);
+            }
+            let mut value = 0u8;
+            let mut mask = 0u8;
+            for nibble in nibbles {
+                value <<= 4;
+                mask <<= 4;
+                if nibble != '?' {
+                    value |= nibble.to_digit(16).ok_or(Error::InvalidSearchPattern)? as u8;
+                    mask |= 0xf;
+                }
+            }
+            values.push(value);
+            masks.push(mask);
+        }
+        Self::new(&values, &masks)
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn is_match(&self, data: &[u8]) -> bool {
+        data.len() >= self.len()
+            && self
+                .values
+                .iter()
+                .zip(&self.masks)
+                .zip(data)
+                .all(|((value, mask), byte)| byte & mask == *value)
+    }
+}
+
+// Unlike needles, matches of a byte pattern may overlap.
+impl SearchPattern for BytePattern {
+    fn max_match_len(&self) -> usize {
+        self.len()
+    }
+
+    fn find_iter<'a>(&'a self, haystack: &'a [u8]) -> Box<dyn Iterator<Item = PatternMatch> + 'a> {
+        let len = self.len();
+        let last_start = match haystack.len().checked_sub(self.len()) {
+            Some(last_start) => last_start,
+            None => return Box::new(std::iter::empty()),
+        };
+        match &self.anchor {
+            Some((anchor_offset, finder)) => {
+                let mut next_anchor = *anchor_offset;
+                Box::new(std::iter::from_fn(move || loop {
+                    if next_anchor > last_start + anchor_offset {
+                        return None;
+                    }
+                    let found = next_anchor + finder.find(&haystack[next_anchor..])?;
+                    next_anchor = found + 1;
+                    let start = found - anchor_offset;
+                    if start <= last_start && self.is_match(&haystack[start..]) {
+                        return Some(PatternMatch::new(start..start + len));
+                    }
+                }))
+            }
+            None => Box::new(
+                (0..=last_start)
+                    .filter(move |start| self.is_match(&haystack[*start..]))
+                    .map(move |start| PatternMatch::new(start..start + len)),
+            ),
+        }
+    }
+
+    fn has_overlapping_matches(&self) -> bool {
+        true
+    }
+}
+
+// A regular expression matching arbitrary bytes. Matches are searched in chunks of memory,
+// thus matches longer than max_match_len may be cut short at the end of a chunk. The chunks
+// overlap by max_match_len, which is why it can't exceed the chunk size.
+#[derive(Clone, Debug)]
+pub struct RegexPattern {
+    regex: Regex,
+    max_match_len: usize,
+}
+
+impl RegexPattern {
+    pub fn new(pattern: &str, max_match_len: usize) -> Result<Self, Error> {
+        if max_match_len == 0 || max_match_len as u64 > SEARCH_CHUNK_SIZE {
+            return Err(Error::InvalidSearchPattern);
+        }
+        let regex = Regex::new(pattern).map_err(|_| Error::InvalidSearchPattern)?;
+        Ok(Self {
+            regex,
+            max_match_len,
+        })
+    }
+}
+
+// Non-overlapping leftmost-first matches, as the regex crate reports them.
+impl SearchPattern for RegexPattern {
+    fn max_match_len(&self) -> usize {
+        self.max_match_len
+    }
+
+    fn find_iter<'a>(&'a self, haystack: &'a [u8]) -> Box<dyn Iterator<Item = PatternMatch> + 'a> {
+        Box::new(
+            self.regex
+                .find_iter(haystack)
+                .map(|found| PatternMatch::new(found.range())),
+        )
+    }
+}
+
+// Several needles searched for in a single pass. All occurrences of every needle are
+// reported, even if they overlap occurrences of other needles.
+#[derive(Clone, Debug)]
+pub struct MultiNeedlePattern {
+    automaton: AhoCorasick,
+    max_needle_len: usize,
+}
+
+impl MultiNeedlePattern {
+    pub fn new<T: AsRef<[u8]>>(needles: &[T]) -> Result<Self, Error> {
+        if needles.is_empty() || needles.iter().any(|needle| needle.as_ref().is_empty()) {
+            return Err(Error::InvalidSearchPattern);
+        }
+        let automaton = AhoCorasick::new(needles).map_err(|_| Error::InvalidSearchPattern)?;
+        Ok(Self {
+            automaton,
+            max_needle_len: needles
+                .iter()
+                .map(|needle| needle.as_ref().len())
+                .max()
+                .unwrap(),
+        })
+    }
+}
+
+impl SearchPattern for MultiNeedlePattern {
+    fn max_match_len(&self) -> usize {
+        self.max_needle_len
+    }
+
+    fn find_iter<'a>(&'a self, haystack: &'a [u8]) -> Box<dyn Iterator<Item = PatternMatch> + 'a> {
+        // Overlapping matches are reported by their end, but are expected by their start.
+        let mut matches: Vec<PatternMatch> = self
+            .automaton
+            .find_overlapping_iter(haystack)
+            .map(|found| PatternMatch {
+                range: found.range(),
+                needle_index: found.pattern().as_usize(),
+            })
+            .collect();
+        matches.sort_by_key(|found| (found.range.start, found.needle_index));
+        Box::new(matches.into_iter())
+    }
+
+    fn has_overlapping_matches(&self) -> bool {
+        true
+    }
+}
+
+// Little-endian pointers whose value is in [va_start, va_end), checked at every offset.
+#[derive(Clone, Debug)]
+pub struct PointerPattern {
+    pointer_size: usize,
+    va_start: u64,
+    va_end: u64,
+    // Merged and sorted inclusive intervals of mapped addresses
+    mapped_intervals: Option<Vec<(u64, u64)>>,
+}
+
+impl PointerPattern {
+    pub fn new(pointer_size: usize, va_start: u64, va_end: u64) -> Result<Self, Error> {
+        if (pointer_size != 4 && pointer_size != 8) || va_start >= va_end {
+            return Err(Error::InvalidSearchPattern);
+        }
+        Ok(Self {
+            pointer_size,
+            va_start,
+            va_end,
+            mapped_intervals: None,
+        })
+    }
+
+    // Only values which are mapped by one of the ranges match.
+    pub fn restrict_to_mapped<RangeType: GenericPageRange>(mut self, ranges: &[RangeType]) -> Self {
+        let mut intervals: Vec<(u64, u64)> = ranges
+            .iter()
+            .filter(|range| range.get_va_extent() > 0)
+            .map(|range| {
+                let va = range.get_va_start();
+                (va, va + (range.get_va_extent() - 1))
+            })
+            .collect();
+        intervals.sort();
+        let mut merged: Vec<(u64, u64)> = vec![];
+        for (first, last) in intervals {
+            match merged.last_mut() {
+                Some(previous) if previous.1 == u64::MAX || previous.1 + 1 >= first => {
+                    previous.1 = previous.1.max(last);
+                }
+                _ => merged.push((first, last)),
+            }
+        }
+        self.mapped_intervals = Some(merged);
+        self
+    }
+
+    pub fn is_match(&self, value: u64) -> bool {
+        if value < self.va_start || value >= self.va_end {
+            return false;
+        }
+        match &self.mapped_intervals {
+            Some(intervals) => {
+                let index = intervals.partition_point(|interval| interval.1 < value);
+                intervals
+                    .get(index)
+                    .is_some_and(|interval| interval.0 <= value)
+            }
+            None => true,
+        }
+    }
+
+    fn read_value(&self, data: &[u8]) -> u64 {
+        match self.pointer_size {
+            4 => u32::from_le_bytes(data[..4].try_into().unwrap()) as u64,
+            8 => u64::from_le_bytes(data[..8].try_into().unwrap()),
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl SearchPattern for PointerPattern {
+    fn max_match_len(&self) -> usize {
+        self.pointer_size
+    }
+
+    fn find_iter<'a>(&'a self, haystack: &'a [u8]) -> Box<dyn Iterator<Item = PatternMatch> + 'a> {
+        let last_start = match haystack.len().checked_sub(self.pointer_size) {
+            Some(last_start) => last_start,
+            None => return Box::new(std::iter::empty()),
+        };
+        Box::new(
+            (0..=last_start)
+                .filter(move |start| self.is_match(self.read_value(&haystack[*start..])))
+                .map(move |start| PatternMatch::new(start..start + self.pointer_size)),
+        )
+    }
+
+    fn has_overlapping_matches(&self) -> bool {
+        true
+    }
+}
+
+// Size of the blocks which are read and searched at once.
+const SEARCH_CHUNK_SIZE: u64 = 16 * 1024 * 1024;
+
+// A window into the virtually contiguous memory of a single page range.
+struct SearchChunk {
+    range_index: usize,
+    offset: u64, // From the start of the range
+    extent: u64,
+    read_size: u64, // Overlaps into the next chunk of the same range
+}
+
+fn split_into_chunks<RangeType: GenericPageRange>(
+    ranges: &[RangeType],
+    chunk_size: u64,
+    overlap: u64,
+) -> Vec<SearchChunk> {
+    let mut chunks = vec![];
+    for (range_index, range) in ranges.iter().enumerate() {
+        let range_extent = range.get_va_extent();
+        let mut offset = 0;
+        while offset < range_extent {
+            let extent = chunk_size.min(range_extent - offset);
+            chunks.push(SearchChunk {
+                range_index,
+                offset,
+                extent,
+                read_size: extent.saturating_add(overlap).min(range_extent - offset),
+            });
+            offset += extent;
+        }
+    }
+    chunks
+}
+
+// Reads the virtually contiguous block at the given offset of the range. The physical ranges
+// back the range in VA order, a frame mapped twice shows up twice.
+fn read_range_block<RangeType: GenericPageRange>(
+    memory_view: &mut dyn MemoryView,
+    range: &RangeType,
+    offset: u64,
+    size: u64,
+) -> Result<Vec<u8>, Error> {
+    let mut block = vec![0u8; size as usize];
+    let end = offset + size;
+    let mut phys_range_offset = 0;
+    for phys_range in range.get_phys_ranges().iter() {
+        let phys_range_end = phys_range_offset + phys_range.phys_extent;
+        let read_start = offset.max(phys_range_offset);
+        let read_end = end.min(phys_range_end);
+        if read_start < read_end {
+            memory_view.read_block_inplace(
+                (phys_range.phys_base + (read_start - phys_range_offset)) as usize,
+                (read_end - read_start) as usize,
+                &mut block[(read_start - offset) as usize..(read_end - offset) as usize],
+            )?;
+        }
+        if phys_range_end >= end {
+            return Ok(block);
+        }
+        phys_range_offset = phys_range_end;
+    }
+    // The physical ranges don't cover the whole range.
+    Err(Error::FailedToReadBlock)
+}
+
+// Where the last match of a pattern without overlapping matches ends, as an offset into the
+// range at range_index.
+#[derive(Clone, Copy)]
+struct MatchEnd {
+    range_index: usize,
+    offset: u64,
+}
+
+// A single pass over a range doesn't find matches which start before the end of the previous
+// match. Such a match may cross into the next chunk, which then has to skip its start.
+fn get_chunk_skip(chunk: &SearchChunk, previous_match_end: Option<MatchEnd>) -> u64 {
+    match previous_match_end {
+        Some(match_end) if match_end.range_index == chunk.range_index => {
+            match_end.offset.saturating_sub(chunk.offset)
+        }
+        _ => 0,
+    }
+}
+
+fn search_chunk<RangeType: GenericPageRange, P: SearchPattern + ?Sized>(
+    pattern: &P,
+    memory_view: &mut dyn MemoryView,
+    ranges: &[RangeType],
+    chunk: &SearchChunk,
+    skip: u64,
+    alignment: u64,
+    max_num_occurrences: usize,
+) -> (Vec<SearchResultOccurrence>, Option<MatchEnd>) {
+    let mut occurrences = vec![];
+    let mut match_end = None;
+    let range = &ranges[chunk.range_index];
+    // Chunks which can't be read are skipped.
+    let block = read_range_block(memory_view, range, chunk.offset, chunk.read_size);
+    if let Ok(block_ok) = block {
+        for found in pattern.find_iter(&block_ok[skip as usize..]) {
+            let start = skip + found.range.start as u64;
+            // Occurrences starting in the overlap belong to the next chunk.
+            if start >= chunk.extent {
+                break;
+            }
+            // Unaligned matches hide later matches as well.
+            if !pattern.has_overlapping_matches() {
+                match_end = Some(MatchEnd {
+                    range_index: chunk.range_index,
+                    offset: chunk.offset + skip + found.range.end as u64,
+                });
+            }
+            let va_addr = range.get_va_start() + chunk.offset + start;
+            if !va_addr.is_multiple_of(alignment) {
+                continue;
+            }
+            occurrences.push(SearchResultOccurrence {
+                va: va_addr,
+                length: found.range.len() as u64,
+                range_index: chunk.range_index,
+                needle_index: found.needle_index,
+            });
+            if occurrences.len() >= max_num_occurrences {
+                break;
+            }
+        }
+    }
+    (occurrences, match_end)
+}
+
+// Searches the virtually contiguous memory of every range, thus matches crossing physically
+// discontiguous parts of a range are found as well.
+pub fn search_memory_pattern<RangeType: GenericPageRange, P: SearchPattern + ?Sized>(
+    pattern: &P,
+    ranges: &[RangeType],
     memory_view: &mut dyn MemoryView,
     alignment: Option<u64>,
     max_num_occurrences: usize,
@@ -41,31 +528,247 @@ pub fn search_memory_generic<RangeType: GenericPageRange>(
     if max_num_occurrences == 0 {
         return result;
     }
-    let alignment = if let Some(a) = alignment { a } else { 1 };
-    let mut num_found = 0;
-    'done: for (range_index, range) in ranges.iter().enumerate() {
-        let mut va_off = 0;
-        for phys_range in range.get_phys_ranges().iter() {
-            let block = memory_view.read_block(
-                phys_range.phys_base as usize,
-                phys_range.phys_extent as usize,
-            );
-            if let Ok(block_ok) = block {
-                let it = memmem::find_iter(&block_ok[..], &needle);
-                for found_offset in it {
-                    let va_addr = range.get_va_start() + (found_offset as u64) + va_off;
-                    if va_addr % alignment != 0 {
-                        continue;
-                    }
-                    result.add_result(range_index, va_addr);
-                    num_found += 1;
-                    if num_found >= max_num_occurrences {
-                        break 'done;
-                    }
-                }
-            }
-            va_off += phys_range.phys_extent;
+    let alignment = alignment.unwrap_or(1);
+    let overlap = pattern.max_match_len().saturating_sub(1) as u64;
+    let mut match_end = None;
+    for chunk in split_into_chunks(ranges, SEARCH_CHUNK_SIZE, overlap) {
+        let (occurrences, chunk_match_end) = search_chunk(
+            pattern,
+            memory_view,
+            ranges,
+            &chunk,
+            get_chunk_skip(&chunk, match_end),
+            alignment,
+            max_num_occurrences - result.occurrences.len(),
+        );
+        match_end = chunk_match_end;
+        result.add_chunk_results(occurrences, max_num_occurrences);
+        if result.occurrences.len() >= max_num_occurrences {
+            break;
         }
     }
     result
 }
+
+// Searches the ranges of a page table walk while they are produced, so that the ranges of the
+// whole address space aren't collected first. Only the ranges with occurrences are kept, the
+// range indices of the result refer to them. Tables which can't be read are skipped, as in
+// x86::collect_pages.
+pub fn search_memory_pattern_walk<RangeType, P, I>(
+    pattern: &P,
+    walk: I,
+    memory_view: &mut dyn MemoryView,
+    alignment: Option<u64>,
+    max_num_occurrences: usize,
+) -> (Vec<RangeType>, SearchResult)
+where
+    RangeType: GenericPageRange,
+    P: SearchPattern + ?Sized,
+    I: Iterator<Item = Result<RangeType, Error>>,
+{
+    let mut found_ranges = vec![];
+    let mut result = SearchResult::new();
+    for range in walk.filter_map(Result::ok) {
+        if result.occurrences.len() >= max_num_occurrences {
+            break;
+        }
+        let range_result = search_memory_pattern(
+            pattern,
+            std::slice::from_ref(&range),
+            memory_view,
+            alignment,
+            max_num_occurrences - result.occurrences.len(),
+        );
+        if range_result.occurrences.is_empty() {
+            continue;
+        }
+        for mut occurrence in range_result.occurrences {
+            occurrence.range_index = found_ranges.len();
+            result.occurrences.push(occurrence);
+        }
+        found_ranges.push(range);
+    }
+    (found_ranges, result)
+}
+
+pub fn search_memory_walk<RangeType: GenericPageRange, I>(
+    needle: &[u8],
+    walk: I,
+    memory_view: &mut dyn MemoryView,
+    alignment: Option<u64>,
+    max_num_occurrences: usize,
+) -> (Vec<RangeType>, SearchResult)
+where
+    I: Iterator<Item = Result<RangeType, Error>>,
+{
+    search_memory_pattern_walk(
+        &memmem::Finder::new(needle),
+        walk,
+        memory_view,
+        alignment,
+        max_num_occurrences,
+    )
+}
+
+pub fn search_memory_generic<RangeType: GenericPageRange>(
+    needle: &[u8],
+    ranges: &[RangeType],
+    memory_view: &mut dyn MemoryView,
+    alignment: Option<u64>,
+    max_num_occurrences: usize,
+) -> SearchResult {
+    search_memory_pattern(
+        &memmem::Finder::new(needle),
+        ranges,
+        memory_view,
+        alignment,
+        max_num_occurrences,
+    )
+}
+
+// Every range is read once, no matter how many needles are searched for.
+pub fn search_memory_multi<RangeType: GenericPageRange, T: AsRef<[u8]>>(
+    needles: &[T],
+    ranges: &[RangeType],
+    memory_view: &mut dyn MemoryView,
+    alignment: Option<u64>,
+    max_num_occurrences: usize,
+) -> Result<SearchResult, Error> {
+    Ok(search_memory_pattern(
+        &MultiNeedlePattern::new(needles)?,
+        ranges,
+        memory_view,
+        alignment,
+        max_num_occurrences,
+    ))
+}
+
+// Same as search_memory_pattern, but the chunks are read and scanned in parallel, each thread
+// with its own clone of the memory view. The occurrences are in the same order as in
+// search_memory_pattern.
+pub fn search_memory_pattern_parallel<
+    RangeType: GenericPageRange + Sync,
+    P: SearchPattern + ?Sized,
+    M: MemoryView + Clone,
+>(
+    pattern: &P,
+    ranges: &[RangeType],
+    memory_view: &M,
+    alignment: Option<u64>,
+    max_num_occurrences: usize,
+) -> SearchResult {
+    search_pattern_parallel_chunked(
+        pattern,
+        ranges,
+        memory_view,
+        alignment,
+        max_num_occurrences,
+        SEARCH_CHUNK_SIZE,
+    )
+}
+
+pub fn search_memory_parallel<RangeType: GenericPageRange + Sync, M: MemoryView + Clone>(
+    needle: &[u8],
+    ranges: &[RangeType],
+    memory_view: &M,
+    alignment: Option<u64>,
+    max_num_occurrences: usize,
+) -> SearchResult {
+    search_memory_pattern_parallel(
+        &memmem::Finder::new(needle),
+        ranges,
+        memory_view,
+        alignment,
+        max_num_occurrences,
+    )
+}
+
+// Chunk results which are merged in order as soon as all earlier chunks are done.
+struct ChunkMerger {
+    result: SearchResult,
+    pending: Vec<Option<(Vec<SearchResultOccurrence>, Option<MatchEnd>)>>,
+    next_chunk: usize,
+    match_end: Option<MatchEnd>,
+}
+
+pub(crate) fn search_pattern_parallel_chunked<
+    RangeType: GenericPageRange + Sync,
+    P: SearchPattern + ?Sized,
+    M: MemoryView + Clone,
+>(
+    pattern: &P,
+    ranges: &[RangeType],
+    memory_view: &M,
+    alignment: Option<u64>,
+    max_num_occurrences: usize,
+    chunk_size: u64,
+) -> SearchResult {
+    if max_num_occurrences == 0 {
+        return SearchResult::new();
+    }
+    let alignment = alignment.unwrap_or(1);
+    let overlap = pattern.max_match_len().saturating_sub(1) as u64;
+    let chunks = split_into_chunks(ranges, chunk_size, overlap);
+    // Chunks from the cutoff on aren't searched, since earlier chunks filled the result.
+    let cutoff = AtomicUsize::new(usize::MAX);
+    let merger = Mutex::new(ChunkMerger {
+        result: SearchResult::new(),
+        pending: (0..chunks.len()).map(|_| None).collect(),
+        next_chunk: 0,
+        match_end: None,
+    });
+    chunks.par_iter().enumerate().for_each_with(
+        memory_view.clone(),
+        |memory_view, (chunk_index, chunk)| {
+            if chunk_index >= cutoff.load(Ordering::Relaxed) {
+                return;
+            }
+            let chunk_result = search_chunk(
+                pattern,
+                memory_view,
+                ranges,
+                chunk,
+                0,
+                alignment,
+                max_num_occurrences,
+            );
+
+            let mut guard = merger.lock().unwrap();
+            let merger = &mut *guard;
+            merger.pending[chunk_index] = Some(chunk_result);
+            while merger.next_chunk < merger.pending.len()
+                && merger.result.occurrences.len() < max_num_occurrences
+            {
+                let (mut occurrences, mut match_end) =
+                    match merger.pending[merger.next_chunk].take() {
+                        Some(chunk_result) => chunk_result,
+                        None => break,
+                    };
+                // The chunk was searched from its start, but a match of an earlier chunk
+                // crosses into it.
+                let next_chunk = &chunks[merger.next_chunk];
+                let skip = get_chunk_skip(next_chunk, merger.match_end);
+                if skip > 0 {
+                    (occurrences, match_end) = search_chunk(
+                        pattern,
+                        memory_view,
+                        ranges,
+                        next_chunk,
+                        skip,
+                        alignment,
+                        max_num_occurrences - merger.result.occurrences.len(),
+                    );
+                }
+                merger.match_end = match_end;
+                merger
+                    .result
+                    .add_chunk_results(occurrences, max_num_occurrences);
+                merger.next_chunk += 1;
+            }
+            if merger.result.occurrences.len() >= max_num_occurrences {
+                cutoff.fetch_min(merger.next_chunk, Ordering::Relaxed);
+            }
+        },
+    );
+    merger.into_inner().unwrap().result
+}
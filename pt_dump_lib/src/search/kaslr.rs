@@ -0,0 +1,179 @@
+use crate::memory::memory::MemoryView;
+use crate::pt::arm::ArmPageRange;
+use crate::pt::page_range::{GenericPagePermissions, GenericPageRange};
+use crate::pt::riscv::RiscvPageRange;
+use crate::pt::x86::X86PageRange;
+use crate::search::bytes_search::{search_memory_pattern, BytePattern, SearchPattern};
+use crate::search::reverse_mapping;
+use memchr::memmem;
+
+const LINUX_IMAGE_ALIGNMENT: u64 = 2 * 1024 * 1024;
+const PAGE_SIZE: u64 = 0x1000;
+// Both the arm64 and the RISC-V Image header have a magic at offset 0x38, see
+// Documentation/arm64/booting.rst and Documentation/riscv/boot-image-header.rst.
+const IMAGE_MAGIC_OFFSET: usize = 0x38;
+const ARM64_IMAGE_MAGIC: &[u8] = b"ARM\x64";
+const RISCV_IMAGE_MAGIC: &[u8] = b"RSC\x05";
+// The kernel is never loaded below 1 MiB on x86-32.
+const X86_32_MIN_IMAGE_PHYS: u64 = 0x100000;
+// startup_32 in arch/x86/kernel/head_32.S begins with movl pa(initial_stack), %ecx.
+const X86_32_IMAGE_START: &[u8] = &[0x8b, 0x0d];
+
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct KaslrInfo {
+    pub image_virt: Option<u64>,
+    pub image_phys: Option<u64>,
+    // The virtual address of physical address 0 in the direct mapping of physical memory,
+    // i.e. page_offset_base on x86-64. The VA of a physical address pa is physmap_virt + pa.
+    pub physmap_virt: Option<u64>,
+}
+
+fn va_to_pa<RangeType: GenericPageRange>(range: &RangeType, va: u64) -> Option<u64> {
+    let mut remaining_off = va.checked_sub(range.get_va_start())?;
+    for phys_range in range.get_phys_ranges() {
+        if phys_range.phys_extent > remaining_off {
+            return Some(phys_range.phys_base + remaining_off);
+        }
+        remaining_off -= phys_range.phys_extent;
+    }
+    None
+}
+
+fn kernel_only_ranges<RangeType: GenericPagePermissions + Clone>(
+    ranges: &[RangeType],
+    executable: bool,
+) -> Vec<RangeType> {
+    ranges
+        .iter()
+        .filter(|range| !range.is_user_readable() && range.is_kernel_executable() == executable)
+        .cloned()
+        .collect()
+}
+
+// Returns the VA and PA of the first aligned match of the pattern.
+fn find_first<RangeType: GenericPageRange, P: SearchPattern + ?Sized>(
+    pattern: &P,
+    ranges: &[RangeType],
+    memory_view: &mut dyn MemoryView,
+    alignment: u64,
+) -> Option<(u64, Option<u64>)> {
+    let search_result = search_memory_pattern(pattern, ranges, memory_view, Some(alignment), 1);
+    let occ = search_result.get_results().first()?;
+    Some((occ.va, va_to_pa(&ranges[occ.range_index], occ.va)))
+}
+
+pub fn find_kaslr_linux_x86_64(
+    ranges: &[X86PageRange],
+    memory_view: &mut dyn MemoryView,
+) -> KaslrInfo {
+    let mut kaslr_info = KaslrInfo::default();
+
+    // Find 2-MiB-aligned supervisor pages which have the byte 0x48 at the beginning. The
+    // first executable one is the kernel image, the first non-executable one the physmap.
+    let linux_base_needle = memmem::Finder::new(&[0x48_u8]);
+    let executable_ranges = kernel_only_ranges(ranges, true);
+    if let Some((image_virt, image_phys)) = find_first(
+        &linux_base_needle,
+        &executable_ranges,
+        memory_view,
+        LINUX_IMAGE_ALIGNMENT,
+    ) {
+        kaslr_info.image_virt = Some(image_virt);
+        kaslr_info.image_phys = image_phys;
+    }
+
+    let data_ranges = kernel_only_ranges(ranges, false);
+    if let Some((physmap_virt, _)) = find_first(
+        &linux_base_needle,
+        &data_ranges,
+        memory_view,
+        LINUX_IMAGE_ALIGNMENT,
+    ) {
+        kaslr_info.physmap_virt = Some(physmap_virt);
+    }
+    kaslr_info
+}
+
+// The kernel image is the first executable 2-MiB-aligned supervisor page above the low 1 MiB
+// which starts like startup_32. Without PAE there is no NX bit and the whole direct mapping is
+// executable, hence the check of the first instruction. The kernel lives in the direct mapping
+// on x86-32, which gives the physmap.
+pub fn find_kaslr_linux_x86_32(
+    ranges: &[X86PageRange],
+    memory_view: &mut dyn MemoryView,
+) -> KaslrInfo {
+    let mut kaslr_info = KaslrInfo::default();
+    for range in kernel_only_ranges(ranges, true) {
+        let range_end = range.get_va() + range.get_extent();
+        let mut va = (range.get_va() + LINUX_IMAGE_ALIGNMENT - 1) & !(LINUX_IMAGE_ALIGNMENT - 1);
+        while va < range_end {
+            let pa = range.gva_to_gpa(va).filter(|pa| {
+                *pa >= X86_32_MIN_IMAGE_PHYS
+                    && memory_view
+                        .read_block(*pa as usize, X86_32_IMAGE_START.len())
+                        .is_ok_and(|block| block == X86_32_IMAGE_START)
+            });
+            if let Some(pa) = pa {
+                kaslr_info.image_virt = Some(va);
+                kaslr_info.image_phys = Some(pa);
+                kaslr_info.physmap_virt = Some(va.wrapping_sub(pa));
+                return kaslr_info;
+            }
+            va += LINUX_IMAGE_ALIGNMENT;
+        }
+    }
+    kaslr_info
+}
+
+// Finds the first executable kernel page with the given Image header magic and the linear
+// map alias of its physical address.
+fn find_kaslr_linux_from_image_header<RangeType>(
+    ranges: &[RangeType],
+    memory_view: &mut dyn MemoryView,
+    magic: &[u8],
+) -> KaslrInfo
+where
+    RangeType: GenericPageRange + GenericPagePermissions + Clone,
+{
+    let mut kaslr_info = KaslrInfo::default();
+
+    let mut values = vec![0u8; IMAGE_MAGIC_OFFSET];
+    values.extend_from_slice(magic);
+    let mut masks = vec![0u8; IMAGE_MAGIC_OFFSET];
+    masks.resize(values.len(), 0xff);
+    let header_pattern = BytePattern::new(&values, &masks).unwrap();
+
+    // The image is 2 MiB aligned, but older arm64 kernels have a TEXT_OFFSET of 0x80000.
+    let executable_ranges = kernel_only_ranges(ranges, true);
+    let (image_virt, image_phys) =
+        match find_first(&header_pattern, &executable_ranges, memory_view, PAGE_SIZE) {
+            Some(image_info) => image_info,
+            None => return kaslr_info,
+        };
+    kaslr_info.image_virt = Some(image_virt);
+    kaslr_info.image_phys = image_phys;
+
+    // The linear map also covers the kernel image, but without execute permissions.
+    if let Some(image_phys) = image_phys {
+        let data_ranges = kernel_only_ranges(ranges, false);
+        let linear_alias = reverse_mapping::find_virtual_addresses(&data_ranges, image_phys, 1)
+            .into_iter()
+            .min_by_key(|mapping| mapping.va);
+        kaslr_info.physmap_virt = linear_alias.map(|mapping| mapping.va.wrapping_sub(image_phys));
+    }
+    kaslr_info
+}
+
+pub fn find_kaslr_linux_aarch64(
+    ranges: &[ArmPageRange],
+    memory_view: &mut dyn MemoryView,
+) -> KaslrInfo {
+    find_kaslr_linux_from_image_header(ranges, memory_view, ARM64_IMAGE_MAGIC)
+}
+
+pub fn find_kaslr_linux_riscv64(
+    ranges: &[RiscvPageRange],
+    memory_view: &mut dyn MemoryView,
+) -> KaslrInfo {
+    find_kaslr_linux_from_image_header(ranges, memory_view, RISCV_IMAGE_MAGIC)
+}
@@ -0,0 +1,48 @@
+use crate::pt::page_range::GenericPageRange;
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct ReverseMapping {
+    pub va: u64,
+    pub pa: u64,
+    pub extent: u64,
+    pub range_index: usize,
+}
+
+fn last_address(base: u64, extent: u64) -> Option<u64> {
+    base.checked_add(extent.checked_sub(1)?)
+}
+
+// Returns every virtual address interval which maps a part of [pa, pa + extent).
+// The attributes of a mapping are the ones of the range at range_index.
+pub fn find_virtual_addresses<RangeType: GenericPageRange>(
+    ranges: &[RangeType],
+    pa: u64,
+    extent: u64,
+) -> Vec<ReverseMapping> {
+    let mut result = vec![];
+    // Empty intervals and intervals past the end of the address space don't map anything.
+    let pa_last = match last_address(pa, extent) {
+        Some(pa_last) => pa_last,
+        None => return result,
+    };
+    for (range_index, range) in ranges.iter().enumerate() {
+        let mut va_off = 0;
+        for phys_range in range.get_phys_ranges().iter() {
+            let phys_last = last_address(phys_range.phys_base, phys_range.phys_extent);
+            if let Some(phys_last) =
+                phys_last.filter(|phys_last| phys_range.phys_base <= pa_last && pa <= *phys_last)
+            {
+                let overlap_begin = phys_range.phys_base.max(pa);
+                let overlap_last = phys_last.min(pa_last);
+                result.push(ReverseMapping {
+                    va: range.get_va_start() + va_off + (overlap_begin - phys_range.phys_base),
+                    pa: overlap_begin,
+                    extent: overlap_last - overlap_begin + 1,
+                    range_index,
+                });
+            }
+            va_off += phys_range.phys_extent;
+        }
+    }
+    result
+}
@@ -0,0 +1,253 @@
+use crate::memory::memory::MemoryView;
+use crate::pt::common::{Error, PhysRange};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+const PAGE_SIZE: u64 = 0x1000;
+const NUM_ENTRIES: usize = 512;
+// Physical memory is read in blocks of this size while scanning.
+const SCAN_BLOCK_SIZE: u64 = 2 * 1024 * 1024;
+
+// The kind of root table to look for. x86-64 means a 4-level PML4, AArch64 means a level 0
+// table of the 4K granule with 48-bit output addresses.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum RootScanFlavour {
+    X86_64,
+    Aarch64,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct RootCandidate {
+    pub pa: u64,
+    pub score: u64,
+    pub present_entries: usize,
+    // Entries in the lower and the upper half of the table, x86-64 only. An AArch64 root
+    // belongs to either TTBR0 or TTBR1, which the index of an entry doesn't tell.
+    pub user_entries: usize,
+    pub kernel_entries: usize,
+    pub self_reference: bool, // One of the entries points back to the table
+    // Number of other candidates with identical kernel halves, x86-64 only
+    pub shared_kernel_half: usize,
+}
+
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct RootValidation {
+    pub tables_visited: usize,
+    pub table_entries: usize,
+    pub leaf_entries: usize,
+    // Entries with reserved encodings or pointing to tables outside of physical memory
+    pub invalid_entries: usize,
+    pub truncated: bool, // The walk hit the table limit
+}
+
+impl RootValidation {
+    pub fn is_plausible(&self) -> bool {
+        self.invalid_entries == 0 && self.leaf_entries > 0
+    }
+}
+
+enum EntryKind {
+    Invalid,
+    Table(u64),
+    Leaf,
+    Reserved,
+}
+
+fn is_in_memory(phys_ranges: &[PhysRange], pa: u64) -> bool {
+    phys_ranges
+        .iter()
+        .any(|range| range.phys_base <= pa && pa - range.phys_base < range.phys_extent)
+}
+
+fn parse_x86_64_entry(raw_entry: u64, level: u8) -> EntryKind {
+    if raw_entry & 1 == 0 {
+        return EntryKind::Invalid;
+    }
+    let ps = (raw_entry >> 7) & 1 == 1;
+    match (level, ps) {
+        // PS is reserved in the PML4
+        (0, true) => EntryKind::Reserved,
+        (3, _) | (1, true) | (2, true) => EntryKind::Leaf,
+        _ => EntryKind::Table(raw_entry & 0x000f_ffff_ffff_f000),
+    }
+}
+
+fn parse_aarch64_entry(raw_entry: u64, level: u8) -> EntryKind {
+    match (raw_entry & 0b11, level) {
+        (0b00, _) | (0b10, _) => EntryKind::Invalid,
+        // Blocks are not allowed at level 0 and the encoding is reserved at level 3.
+        (0b01, 0) | (0b01, 3) => EntryKind::Reserved,
+        (0b01, _) | (0b11, 3) => EntryKind::Leaf,
+        _ => {
+            // Bits 51:48 must be zero with 48-bit output addresses.
+            if raw_entry & 0x000f_0000_0000_0000 != 0 {
+                EntryKind::Reserved
+            } else {
+                EntryKind::Table(raw_entry & 0x0000_ffff_ffff_f000)
+            }
+        }
+    }
+}
+
+fn parse_entry(flavour: RootScanFlavour, raw_entry: u64, level: u8) -> EntryKind {
+    match flavour {
+        RootScanFlavour::X86_64 => parse_x86_64_entry(raw_entry, level),
+        RootScanFlavour::Aarch64 => parse_aarch64_entry(raw_entry, level),
+    }
+}
+
+fn read_entries(page: &[u8]) -> impl Iterator<Item = u64> + '_ {
+    page.chunks_exact(8)
+        .map(|entry| u64::from_le_bytes(entry.try_into().unwrap()))
+}
+
+// Returns None if the page can't be a root table.
+fn score_page(
+    flavour: RootScanFlavour,
+    phys_ranges: &[PhysRange],
+    pa: u64,
+    page: &[u8],
+) -> Option<RootCandidate> {
+    let mut candidate = RootCandidate {
+        pa,
+        score: 0,
+        present_entries: 0,
+        user_entries: 0,
+        kernel_entries: 0,
+        self_reference: false,
+        shared_kernel_half: 0,
+    };
+    for (index, raw_entry) in read_entries(page).enumerate() {
+        match parse_entry(flavour, raw_entry, 0) {
+            EntryKind::Invalid => continue,
+            EntryKind::Table(table_pa) => {
+                // Data pages full of small integers would otherwise point to page 0.
+                if table_pa == 0 || !is_in_memory(phys_ranges, table_pa) {
+                    return None;
+                }
+                candidate.self_reference |= table_pa == pa;
+            }
+            EntryKind::Leaf | EntryKind::Reserved => return None,
+        }
+        candidate.present_entries += 1;
+        if flavour == RootScanFlavour::X86_64 {
+            if index < NUM_ENTRIES / 2 {
+                candidate.user_entries += 1;
+            } else {
+                candidate.kernel_entries += 1;
+            }
+        }
+    }
+    if candidate.present_entries == 0 {
+        return None;
+    }
+    Some(candidate)
+}
+
+// Accessed and dirty bits differ between otherwise identical kernel halves.
+fn hash_kernel_half(page: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for raw_entry in read_entries(page).skip(NUM_ENTRIES / 2) {
+        (raw_entry & !0x60).hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+// Scores every page of the given physical memory as a root table and returns the plausible
+// ones, best first. The score counts the present entries, with a bonus for a self-referencing
+// entry and for every other candidate with the same kernel half, as every process shares
+// the kernel mappings on x86-64.
+pub fn scan_for_roots(
+    memory: &mut dyn MemoryView,
+    phys_ranges: &[PhysRange],
+    flavour: RootScanFlavour,
+) -> Vec<RootCandidate> {
+    let mut candidates = vec![];
+    let mut kernel_half_hashes = vec![];
+    for phys_range in phys_ranges {
+        // Only whole pages can be tables.
+        let first_page = (phys_range.phys_base + PAGE_SIZE - 1) & !(PAGE_SIZE - 1);
+        let end = phys_range.phys_base + phys_range.phys_extent;
+        let mut block_pa = first_page;
+        while block_pa + PAGE_SIZE <= end {
+            let block_size = SCAN_BLOCK_SIZE.min((end - block_pa) & !(PAGE_SIZE - 1));
+            // Blocks which can't be read are skipped.
+            if let Ok(block) = memory.read_block(block_pa as usize, block_size as usize) {
+                for (page_index, page) in block.chunks_exact(PAGE_SIZE as usize).enumerate() {
+                    let pa = block_pa + page_index as u64 * PAGE_SIZE;
+                    if let Some(candidate) = score_page(flavour, phys_ranges, pa, page) {
+                        if flavour == RootScanFlavour::X86_64 && candidate.kernel_entries > 0 {
+                            kernel_half_hashes.push(Some(hash_kernel_half(page)));
+                        } else {
+                            kernel_half_hashes.push(None);
+                        }
+                        candidates.push(candidate);
+                    }
+                }
+            }
+            block_pa += block_size;
+        }
+    }
+
+    let mut kernel_half_counts: HashMap<u64, usize> = HashMap::new();
+    for hash in kernel_half_hashes.iter().flatten() {
+        *kernel_half_counts.entry(*hash).or_insert(0) += 1;
+    }
+    for (candidate, hash) in candidates.iter_mut().zip(kernel_half_hashes) {
+        if let Some(hash) = hash {
+            candidate.shared_kernel_half = kernel_half_counts[&hash] - 1;
+        }
+        candidate.score = candidate.present_entries as u64
+            + 8 * candidate.shared_kernel_half as u64
+            + if candidate.self_reference { 64 } else { 0 };
+    }
+    candidates.sort_by(|a, b| b.score.cmp(&a.score).then(a.pa.cmp(&b.pa)));
+    candidates
+}
+
+// Walks the page table at pa, reading at most max_tables tables, and checks that all tables
+// are within physical memory and that the entries use valid encodings.
+pub fn validate_root(
+    memory: &mut dyn MemoryView,
+    phys_ranges: &[PhysRange],
+    flavour: RootScanFlavour,
+    pa: u64,
+    max_tables: usize,
+) -> Result<RootValidation, Error> {
+    let mut validation = RootValidation::default();
+    let mut tables = vec![(pa, 0u8)];
+    let mut page = vec![0u8; PAGE_SIZE as usize];
+    while let Some((table_pa, level)) = tables.pop() {
+        if validation.tables_visited >= max_tables {
+            validation.truncated = true;
+            break;
+        }
+        let read_result =
+            memory.read_block_inplace(table_pa as usize, PAGE_SIZE as usize, &mut page);
+        if let Err(err) = read_result {
+            if level == 0 {
+                return Err(err);
+            }
+            validation.invalid_entries += 1;
+            continue;
+        }
+        validation.tables_visited += 1;
+        for raw_entry in read_entries(&page) {
+            match parse_entry(flavour, raw_entry, level) {
+                EntryKind::Invalid => {}
+                EntryKind::Table(next_table_pa) => {
+                    if is_in_memory(phys_ranges, next_table_pa) {
+                        validation.table_entries += 1;
+                        tables.push((next_table_pa, level + 1));
+                    } else {
+                        validation.invalid_entries += 1;
+                    }
+                }
+                EntryKind::Leaf => validation.leaf_entries += 1,
+                EntryKind::Reserved => validation.invalid_entries += 1,
+            }
+        }
+    }
+    Ok(validation)
+}
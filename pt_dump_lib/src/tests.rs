@@ -1,6 +1,8 @@
 #[cfg(test)]
 mod common;
 #[cfg(test)]
+mod diff_tests;
+#[cfg(test)]
 mod filter_tests;
 #[cfg(test)]
 mod memory_tests;
@@ -1,6 +1,9 @@
 use crate::memory::memory::MemoryView;
 use crate::pt::common::Error;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 
+#[derive(Clone)]
 pub struct MemoryViewFromArray {
     pub data: Vec<u8>,
 }
@@ -36,3 +39,40 @@ impl MemoryView for MemoryViewFromArray {
         }
     }
 }
+
+// Counts the blocks read by all of its clones.
+#[derive(Clone)]
+pub struct CountingMemoryView {
+    memory: MemoryViewFromArray,
+    reads: Arc<AtomicUsize>,
+}
+
+impl CountingMemoryView {
+    pub fn from(data: &[u8]) -> Self {
+        Self {
+            memory: MemoryViewFromArray::from(data),
+            reads: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    pub fn get_num_reads(&self) -> usize {
+        self.reads.load(Ordering::Relaxed)
+    }
+}
+
+impl MemoryView for CountingMemoryView {
+    fn read_block(&mut self, offset: usize, block_size: usize) -> Result<Vec<u8>, Error> {
+        self.reads.fetch_add(1, Ordering::Relaxed);
+        self.memory.read_block(offset, block_size)
+    }
+
+    fn read_block_inplace(
+        &mut self,
+        offset: usize,
+        block_size: usize,
+        block: &mut [u8],
+    ) -> Result<(), Error> {
+        self.reads.fetch_add(1, Ordering::Relaxed);
+        self.memory.read_block_inplace(offset, block_size, block)
+    }
+}
@@ -0,0 +1,149 @@
+use crate::diff::page_range_diff::{diff_page_ranges, DiffSide, PageRangeChange, PageRangeDiff};
+use crate::pt::common::PhysRange;
+use crate::pt::x86::{PageAttributes, X86PageRange};
+
+fn create_range(va: u64, phys: u64, extent: u64, writeable: bool) -> X86PageRange {
+    X86PageRange::new(
+        va,
+        extent,
+        PageAttributes {
+            accessed: false,
+            dirty: false,
+            writeable: writeable,
+            user: false,
+            pwt: false,
+            pcd: false,
+            pat: false,
+            global: false,
+            nx: true,
+        },
+        vec![PhysRange::new(phys, extent)],
+    )
+}
+
+#[test]
+fn test_diff_x86_ranges() {
+    use crate::print::printer::DiffWriter;
+
+    let old_ranges = vec![
+        create_range(0x10000, 0x1000, 0x3000, true),
+        create_range(0x20000, 0x8000, 0x1000, false),
+    ];
+    let new_ranges = vec![
+        create_range(0x10000, 0x1000, 0x1000, true),
+        create_range(0x11000, 0x9000, 0x1000, true),
+        create_range(0x12000, 0x3000, 0x1000, false),
+        create_range(0x30000, 0xa000, 0x1000, false),
+    ];
+    let side = |pa: u64, range_index: usize| DiffSide { pa, range_index };
+
+    let diffs = diff_page_ranges(&old_ranges, &new_ranges);
+    assert_eq!(
+        vec![
+            PageRangeDiff {
+                va: 0x11000,
+                extent: 0x1000,
+                change: PageRangeChange::Changed {
+                    old: side(0x2000, 0),
+                    new: side(0x9000, 1),
+                    pa_changed: true,
+                    permissions_changed: false,
+                },
+            },
+            PageRangeDiff {
+                va: 0x12000,
+                extent: 0x1000,
+                change: PageRangeChange::Changed {
+                    old: side(0x3000, 0),
+                    new: side(0x3000, 2),
+                    pa_changed: false,
+                    permissions_changed: true,
+                },
+            },
+            PageRangeDiff {
+                va: 0x20000,
+                extent: 0x1000,
+                change: PageRangeChange::Removed {
+                    old: side(0x8000, 1)
+                },
+            },
+            PageRangeDiff {
+                va: 0x30000,
+                extent: 0x1000,
+                change: PageRangeChange::Added {
+                    new: side(0xa000, 3)
+                },
+            },
+        ],
+        diffs
+    );
+
+    // Changed intervals are rendered as a removed and an added line.
+    let mut writer = DiffWriter::new();
+    writer.write_diffs(&diffs, &old_ranges, &new_ranges);
+    let lines: Vec<&str> = writer.get_result().lines().skip(2).collect();
+    assert_eq!(
+        vec!["-", "+", "-", "+", "-", "+"],
+        lines.iter().map(|line| &line[..1]).collect::<Vec<&str>>()
+    );
+}
+
+#[test]
+fn test_diff_merges_adjacent_changes() {
+    // A range which was moved as a whole is reported once even though it is split differently.
+    let old_ranges = vec![
+        create_range(0x10000, 0x1000, 0x1000, true),
+        create_range(0x11000, 0x2000, 0x1000, true),
+    ];
+    let new_ranges = vec![create_range(0x10000, 0x5000, 0x2000, true)];
+    assert_eq!(
+        vec![PageRangeDiff {
+            va: 0x10000,
+            extent: 0x2000,
+            change: PageRangeChange::Changed {
+                old: DiffSide {
+                    pa: 0x1000,
+                    range_index: 0
+                },
+                new: DiffSide {
+                    pa: 0x5000,
+                    range_index: 0
+                },
+                pa_changed: true,
+                permissions_changed: false,
+            },
+        }],
+        diff_page_ranges(&old_ranges, &new_ranges)
+    );
+}
+
+#[test]
+fn test_diff_top_page() {
+    let top_va = 0xfffffffffffff000;
+    let old_ranges = vec![create_range(top_va, 0x1000, 0x1000, true)];
+    let new_ranges = vec![create_range(top_va, 0x2000, 0x1000, true)];
+
+    assert_eq!(
+        Vec::<PageRangeDiff>::new(),
+        diff_page_ranges(&old_ranges, &old_ranges)
+    );
+    assert_eq!(
+        vec![PageRangeDiff {
+            va: top_va,
+            extent: 0x1000,
+            change: PageRangeChange::Changed {
+                old: DiffSide {
+                    pa: 0x1000,
+                    range_index: 0
+                },
+                new: DiffSide {
+                    pa: 0x2000,
+                    range_index: 0
+                },
+                pa_changed: true,
+                permissions_changed: false,
+            },
+        }],
+        diff_page_ranges(&old_ranges, &new_ranges)
+    );
+}
@@ -155,3 +155,246 @@ fn test_x86_filter_has_address() {
         }
     }
 }
+
+#[test]
+fn test_x86_filter_walk_stops_early() {
+    use crate::filter::page_range_filter::filter_x86_walk;
+    use crate::pt::x86::{self, X86Flavour};
+    use crate::tests::common::CountingMemoryView;
+    use byte_slice_cast::*;
+
+    let mut mem = vec![0u64; 512 * 4]; // 4 physical pages
+    mem[1..9].fill(0x1003); // PML4Es, each mapping 512 GiB
+    mem[512] = 0x2003; // PDPTE
+    mem[512 * 2] = 0x200083; // 2 MiB page
+    let mem_as_u8 = mem.as_byte_slice();
+    let mut full_memory_view = CountingMemoryView::from(mem_as_u8);
+    let ranges =
+        x86::collect_pages(X86Flavour::X64, &mut full_memory_view, 0, true, true, false).unwrap();
+    assert_eq!(8, ranges.len());
+
+    let mut filter = PageRangeFilterX86::new();
+    filter.set_va_range(None, Some(0x80_0000_0000 * 3));
+    let mut memory_view = CountingMemoryView::from(mem_as_u8);
+    let walk = x86::walk(X86Flavour::X64, &mut memory_view, 0, true, true, false).unwrap();
+    let filtered_ranges: Vec<X86PageRange> = filter_x86_walk(walk, &filter)
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(filter_x86_ranges(&ranges, &filter), filtered_ranges);
+    assert_eq!(2, filtered_ranges.len());
+    // The tables mapping the ranges past the end of the VA range aren't read.
+    assert!(memory_view.get_num_reads() < full_memory_view.get_num_reads());
+}
+
+#[test]
+fn test_aarch64_filter_global_and_device_memory() {
+    use crate::filter::page_range_filter::filter_aarch64_ranges;
+    use crate::pt::arm::{ArmPageAttributes, ArmPageRange};
+
+    let create_range = |va: u64, not_global: bool, mair_attr: Option<u8>| ArmPageRange {
+        va: va,
+        extent: 0x1000,
+        phys_ranges: Vec::<PhysRange>::new(),
+        attr: ArmPageAttributes {
+            not_global: not_global,
+            mair_attr: mair_attr,
+            ..Default::default()
+        },
+    };
+    let ranges = vec![
+        create_range(0x10000, false, Some(0xff)),
+        create_range(0x20000, true, Some(0x04)),
+        create_range(0x30000, true, None),
+    ];
+
+    let mut filter = PageRangeFilterX86::new();
+    filter.set_global(true);
+    assert_eq!(
+        vec![ranges[0].clone()],
+        filter_aarch64_ranges(&ranges, &filter)
+    );
+    filter.set_global(false);
+    assert_eq!(
+        vec![ranges[1].clone(), ranges[2].clone()],
+        filter_aarch64_ranges(&ranges, &filter)
+    );
+
+    let mut filter = PageRangeFilterX86::new();
+    filter.set_device_memory(true);
+    assert_eq!(
+        vec![ranges[1].clone()],
+        filter_aarch64_ranges(&ranges, &filter)
+    );
+    filter.set_device_memory(false);
+    assert_eq!(
+        vec![ranges[0].clone()],
+        filter_aarch64_ranges(&ranges, &filter)
+    );
+}
+
+#[test]
+fn test_x86_find_aliases() {
+    use crate::filter::alias_analysis::{find_aliases, AliasMapping, PhysicalAlias};
+
+    let create_range = |va: u64, w: bool, u: bool, nx: bool, pcd: bool, phys: Vec<PhysRange>| {
+        let extent = phys.iter().map(|range| range.phys_extent).sum();
+        X86PageRange::new(
+            va,
+            extent,
+            PageAttributes {
+                accessed: false,
+                dirty: false,
+                writeable: w,
+                user: u,
+                pwt: false,
+                pcd: pcd,
+                pat: false,
+                global: false,
+                nx: nx,
+            },
+            phys,
+        )
+    };
+    let ranges = vec![
+        create_range(
+            0xffff800000000000,
+            true,
+            false,
+            true,
+            false,
+            vec![PhysRange::new(0x10000, 0x2000)],
+        ),
+        create_range(
+            0x400000,
+            false,
+            true,
+            false,
+            false,
+            vec![PhysRange::new(0x11000, 0x1000)],
+        ),
+        create_range(
+            0x500000,
+            false,
+            false,
+            true,
+            true,
+            vec![PhysRange::new(0x11000, 0x2000)],
+        ),
+        create_range(
+            0x600000,
+            false,
+            false,
+            true,
+            false,
+            vec![PhysRange::new(0x20000, 0x2000)],
+        ),
+        create_range(
+            0x700000,
+            false,
+            false,
+            true,
+            false,
+            vec![
+                PhysRange::new(0x20000, 0x1000),
+                PhysRange::new(0x21000, 0x1000),
+            ],
+        ),
+    ];
+
+    assert_eq!(
+        vec![
+            PhysicalAlias {
+                pa: 0x11000,
+                extent: 0x1000,
+                mappings: vec![
+                    AliasMapping {
+                        va: 0xffff800000001000,
+                        range_index: 0
+                    },
+                    AliasMapping {
+                        va: 0x400000,
+                        range_index: 1
+                    },
+                    AliasMapping {
+                        va: 0x500000,
+                        range_index: 2
+                    },
+                ],
+                writeable_and_executable: true,
+                user_and_kernel: true,
+                memory_type_conflict: true,
+            },
+            // Adjacent intervals covered by the same mappings are merged.
+            PhysicalAlias {
+                pa: 0x20000,
+                extent: 0x2000,
+                mappings: vec![
+                    AliasMapping {
+                        va: 0x600000,
+                        range_index: 3
+                    },
+                    AliasMapping {
+                        va: 0x700000,
+                        range_index: 4
+                    },
+                ],
+                writeable_and_executable: false,
+                user_and_kernel: false,
+                memory_type_conflict: false,
+            },
+        ],
+        find_aliases(&ranges)
+    );
+}
+
+#[test]
+fn test_x86_find_aliases_in_page_table() {
+    use crate::filter::alias_analysis::{find_aliases, AliasMapping, PhysicalAlias};
+    use crate::pt::x86::{self, X86Flavour};
+    use crate::tests::common::MemoryViewFromArray;
+    use byte_slice_cast::*;
+
+    let mut mem = vec![0u64; 512 * 16]; // 16 physical pages
+    mem[0] = 0x1007; // PML4E
+    mem[512 * 1] = 0x2007; // PDPTE
+    mem[512 * 2] = 0x3007; // PDE
+
+    // Writeable and executable, the same frame twice
+    mem[512 * 3] = 0x5003;
+    mem[512 * 3 + 1] = 0x5003;
+    // Write-back and uncacheable (PCD, PWT)
+    mem[512 * 3 + 0x10] = 0x6003;
+    mem[512 * 3 + 0x11] = 0x601b;
+    // PAT entry 4 and write-back
+    mem[512 * 3 + 0x20] = 0x7083;
+    mem[512 * 3 + 0x21] = 0x7003;
+    let mem_as_u8 = mem.as_byte_slice();
+    let mut memory_view = MemoryViewFromArray::from(&mem_as_u8);
+    let ranges =
+        x86::collect_pages(X86Flavour::X64, &mut memory_view, 0, true, true, false).unwrap();
+    assert_eq!(5, ranges.len());
+
+    let create_alias =
+        |pa: u64, vas: [(u64, usize); 2], w_and_x: bool, conflict: bool| PhysicalAlias {
+            pa: pa,
+            extent: 0x1000,
+            mappings: vas
+                .iter()
+                .map(|(va, range_index)| AliasMapping {
+                    va: *va,
+                    range_index: *range_index,
+                })
+                .collect(),
+            writeable_and_executable: w_and_x,
+            user_and_kernel: false,
+            memory_type_conflict: conflict,
+        };
+    assert_eq!(
+        vec![
+            create_alias(0x5000, [(0x0, 0), (0x1000, 0)], true, false),
+            create_alias(0x6000, [(0x10000, 1), (0x11000, 2)], true, true),
+            create_alias(0x7000, [(0x20000, 3), (0x21000, 4)], true, true),
+        ],
+        find_aliases(&ranges)
+    );
+}
@@ -41,3 +41,104 @@ fn test_memory_view_fd() {
             .unwrap()
     );
 }
+
+#[test]
+fn test_virtual_memory_view() {
+    use crate::memory::memory_virtual::VirtualMemoryView;
+    use crate::pt::common::{Error, PhysRange};
+    use crate::pt::x86::{PageAttributes, X86PageRange};
+    use crate::tests::common::MemoryViewFromArray;
+
+    let data: Vec<u8> = (0usize..0x4000)
+        .map(|i| (i >> 12) as u8 ^ i as u8)
+        .collect();
+    let mut physical_view = MemoryViewFromArray::from(&data[..]);
+    let attr = PageAttributes {
+        accessed: false,
+        dirty: false,
+        writeable: false,
+        user: false,
+        pwt: false,
+        pcd: false,
+        pat: false,
+        global: false,
+        nx: false,
+    };
+    let ranges = vec![
+        X86PageRange::new(
+            0x10000,
+            0x2000,
+            attr.clone(),
+            vec![
+                PhysRange::new(0x3000, 0x1000),
+                PhysRange::new(0x1000, 0x1000),
+            ],
+        ),
+        X86PageRange::new(0x13000, 0x1000, attr, vec![PhysRange::new(0x0, 0x1000)]),
+    ];
+    let mut memory_view = VirtualMemoryView::new(&mut physical_view, &ranges);
+
+    // The read is split across the physical discontinuity.
+    assert_eq!(
+        [&data[0x3ff8..0x4000], &data[0x1000..0x1008]].concat(),
+        memory_view.read_block(0x10ff8, 0x10).unwrap()
+    );
+    assert_eq!(
+        data[0x0..0x1000],
+        memory_view.read_block(0x13000, 0x1000).unwrap()
+    );
+    assert!(matches!(
+        memory_view.read_block(0x11ff8, 0x10),
+        Err(Error::UnmappedMemory)
+    ));
+    assert!(matches!(
+        memory_view.read_block(0x20000, 0x10),
+        Err(Error::UnmappedMemory)
+    ));
+}
+
+#[test]
+fn test_virtual_memory_view_top_page() {
+    use crate::memory::memory_virtual::{collect_virtual_mappings, VirtualMemoryView};
+    use crate::pt::common::{Error, PhysRange};
+    use crate::pt::x86::{PageAttributes, X86PageRange};
+    use crate::tests::common::MemoryViewFromArray;
+
+    let data: Vec<u8> = (0usize..0x3000).map(|i| (i >> 4) as u8).collect();
+    let mut physical_view = MemoryViewFromArray::from(&data[..]);
+    let attr = PageAttributes {
+        accessed: false,
+        dirty: false,
+        writeable: false,
+        user: false,
+        pwt: false,
+        pcd: false,
+        pat: false,
+        global: false,
+        nx: false,
+    };
+    // The last two pages of the address space
+    let ranges = vec![X86PageRange::new(
+        0xffffffffffffe000,
+        0x2000,
+        attr,
+        vec![PhysRange::new(0x2000, 0x1000), PhysRange::new(0x0, 0x1000)],
+    )];
+    assert_eq!(
+        vec![0xffffffffffffe000, 0xfffffffffffff000],
+        collect_virtual_mappings(&ranges)
+            .iter()
+            .map(|mapping| mapping.va)
+            .collect::<Vec<u64>>()
+    );
+
+    let mut memory_view = VirtualMemoryView::new(&mut physical_view, &ranges);
+    assert_eq!(
+        data[0xff0..0x1000],
+        memory_view.read_block(0xfffffffffffffff0, 0x10).unwrap()
+    );
+    assert!(matches!(
+        memory_view.read_block(0xfffffffffffffff0, 0x20),
+        Err(Error::UnmappedMemory)
+    ));
+}
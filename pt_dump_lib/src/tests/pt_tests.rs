@@ -44,8 +44,15 @@ fn test_pt_x86_single_page() {
     let mem_as_u8 = mem.as_byte_slice();
     let mut memory_view = MemoryViewFromArray::from(&mem_as_u8);
     let cr3 = 0u64;
-    let result =
-        x86::collect_pages(x86::X86Flavour::X86, &mut memory_view, cr3, false, false).unwrap();
+    let result = x86::collect_pages(
+        x86::X86Flavour::X86,
+        &mut memory_view,
+        cr3,
+        false,
+        false,
+        false,
+    )
+    .unwrap();
     assert_eq!(
         vec![create_page(false, false, false, 0, 0x2000, 0x1000)],
         result
@@ -62,8 +69,15 @@ fn test_pt_x86_multiple_pages() {
     let mem_as_u8 = mem.as_byte_slice();
     let mut memory_view = MemoryViewFromArray::from(&mem_as_u8);
     let cr3 = 0u64;
-    let result =
-        x86::collect_pages(x86::X86Flavour::X86, &mut memory_view, cr3, false, false).unwrap();
+    let result = x86::collect_pages(
+        x86::X86Flavour::X86,
+        &mut memory_view,
+        cr3,
+        false,
+        false,
+        false,
+    )
+    .unwrap();
     compare_page_vectors(
         &vec![
             create_page(false, false, false, 2 * 4 * 1024 * 1024, 0x2000, 0x1000),
@@ -80,6 +94,75 @@ fn test_pt_x86_multiple_pages() {
     );
 }
 
+#[test]
+fn test_pt_x86_walk() {
+    let mut mem = [0u32; 1024 * 16]; // 16 physical pages
+    mem[1] = 0xfff00001; // PDE, the table can't be read
+    mem[2] = 0x1001; // PDE
+    mem[3] = 0x3001; // PDE
+    mem[4] = 0xfff01001; // PDE, the table can't be read
+    mem[1024 * 1] = 0x2001; // PTE
+    mem[1024 * 1 + 1] = 0x5001; // PTE, coalesced with the previous one
+    mem[1024 * 1 + 3] = 0x4001; // PTE
+    mem[1024 * 3] = 0x6001; // PTE
+    let mem_as_u8 = mem.as_byte_slice();
+    let mut memory_view = MemoryViewFromArray::from(&mem_as_u8);
+    let cr3 = 0u64;
+    let expected = x86::collect_pages(
+        x86::X86Flavour::X86,
+        &mut memory_view,
+        cr3,
+        false,
+        false,
+        false,
+    )
+    .unwrap();
+    assert_eq!(expected.len(), 3);
+    assert_eq!(expected[0].get_phys_ranges().len(), 2);
+
+    // The tables which can't be read are reported in VA order.
+    let walked: Vec<_> = x86::walk(
+        x86::X86Flavour::X86,
+        &mut memory_view,
+        cr3,
+        false,
+        false,
+        false,
+    )
+    .unwrap()
+    .collect();
+    assert_eq!(5, walked.len());
+    assert!(walked[0].is_err());
+    assert!(walked[4].is_err());
+    let walked_ranges: Vec<X86PageRange> = walked.into_iter().filter_map(Result::ok).collect();
+    compare_page_vectors(&expected, &walked_ranges);
+
+    // The walk can be stopped early.
+    let first = x86::walk(
+        x86::X86Flavour::X86,
+        &mut memory_view,
+        cr3,
+        false,
+        false,
+        false,
+    )
+    .unwrap()
+    .find_map(Result::ok)
+    .unwrap();
+    assert_eq!(first, expected[0]);
+
+    // Failing to read the root table fails the whole walk.
+    assert!(x86::walk(
+        x86::X86Flavour::X86,
+        &mut memory_view,
+        0x1000000,
+        false,
+        false,
+        false,
+    )
+    .is_err());
+}
+
 #[test]
 fn test_pt_x86_pde_pdpe() {
     let mut mem = vec![0u32; 1024 * 1024 * 16];
@@ -89,8 +172,15 @@ fn test_pt_x86_pde_pdpe() {
     let mem_as_u8 = mem.as_byte_slice();
     let mut memory_view = MemoryViewFromArray::from(&mem_as_u8);
     let cr3 = 0u64;
-    let result =
-        x86::collect_pages(x86::X86Flavour::X86, &mut memory_view, cr3, true, false).unwrap();
+    let result = x86::collect_pages(
+        x86::X86Flavour::X86,
+        &mut memory_view,
+        cr3,
+        true,
+        false,
+        false,
+    )
+    .unwrap();
     compare_page_vectors(
         &vec![
             create_page(false, false, false, 0, 4 * 1024 * 1024, 0x1000),
@@ -105,7 +195,14 @@ fn test_pt_x86_pde_pdpe() {
         ],
         &result,
     );
-    let result = x86::collect_pages(x86::X86Flavour::X86, &mut memory_view, cr3, false, false);
+    let result = x86::collect_pages(
+        x86::X86Flavour::X86,
+        &mut memory_view,
+        cr3,
+        false,
+        false,
+        false,
+    );
     match result {
         Err(e) => {
             println!("Got error: {:?}", e);
@@ -140,8 +237,15 @@ fn test_pt_x86_all_attributes() {
     let mem_as_u8 = mem.as_byte_slice();
     let mut memory_view = MemoryViewFromArray::from(&mem_as_u8);
     let cr3 = 0u64;
-    let result =
-        x86::collect_pages(x86::X86Flavour::X86, &mut memory_view, cr3, false, false).unwrap();
+    let result = x86::collect_pages(
+        x86::X86Flavour::X86,
+        &mut memory_view,
+        cr3,
+        false,
+        false,
+        false,
+    )
+    .unwrap();
     compare_page_vectors(
         &vec![
             X86PageRange {
@@ -265,7 +369,7 @@ fn test_pt_x86_all_attributes() {
                     accessed: false,
                     dirty: false,
                     global: false,
-                    pat: false,
+                    pat: true, // Bit 7 of a PTE
                     nx: false,
                 },
                 extent: 0x1000,
@@ -354,19 +458,32 @@ fn test_pt_x86_pae() {
     let mem_as_u8 = mem.as_byte_slice();
     let mut memory_view = MemoryViewFromArray::from(&mem_as_u8);
     let cr3 = 0u64;
-    let result = x86::collect_pages(X86Flavour::X86, &mut memory_view, cr3, false, true).unwrap();
+    let result =
+        x86::collect_pages(X86Flavour::X86, &mut memory_view, cr3, false, true, false).unwrap();
+    // Bit 7 of a PTE is PAT
+    let with_pat = |mut page: X86PageRange| {
+        page.attributes.pat = true;
+        page
+    };
     compare_page_vectors(
         &vec![
             create_page(false, false, false, 0, 2 * 1024 * 1024 * 1024, 0x200000), // 2MiB
-            create_page(false, false, false, 2 * 1024 * 1024 + 4096, 0x600000, 4096),
-            create_page(
+            with_pat(create_page(
+                false,
+                false,
+                false,
+                2 * 1024 * 1024 + 4096,
+                0x600000,
+                4096,
+            )),
+            with_pat(create_page(
                 false,
                 false,
                 false,
                 2 * 1024 * 1024 + 3 * 4096,
                 0x800000,
                 4096,
-            ),
+            )),
             create_page(
                 false,
                 false,
@@ -395,8 +512,15 @@ fn test_pt_x86_xn() {
     let mem_as_u8 = mem.as_byte_slice();
     let mut memory_view = MemoryViewFromArray::from(&mem_as_u8);
     let cr3 = 0u64;
-    let result =
-        x86::collect_pages(x86::X86Flavour::X86, &mut memory_view, cr3, true, true).unwrap();
+    let result = x86::collect_pages(
+        x86::X86Flavour::X86,
+        &mut memory_view,
+        cr3,
+        true,
+        true,
+        false,
+    )
+    .unwrap();
     assert_eq!(
         [false, true, true, true],
         [
@@ -418,8 +542,15 @@ fn test_pt_x64_single_page() {
     let mem_as_u8 = mem.as_byte_slice();
     let mut memory_view = MemoryViewFromArray::from(&mem_as_u8);
     let cr3 = 0u64;
-    let result =
-        x86::collect_pages(x86::X86Flavour::X64, &mut memory_view, cr3, false, false).unwrap();
+    let result = x86::collect_pages(
+        x86::X86Flavour::X64,
+        &mut memory_view,
+        cr3,
+        false,
+        false,
+        false,
+    )
+    .unwrap();
     assert_eq!(
         vec![create_page(false, false, false, 0, 0x4000, 0x1000)],
         result
@@ -438,8 +569,15 @@ fn test_pt_x64_xn() {
     let mem_as_u8 = mem.as_byte_slice();
     let mut memory_view = MemoryViewFromArray::from(&mem_as_u8);
     let cr3 = 0u64;
-    let result =
-        x86::collect_pages(x86::X86Flavour::X64, &mut memory_view, cr3, true, true).unwrap();
+    let result = x86::collect_pages(
+        x86::X86Flavour::X64,
+        &mut memory_view,
+        cr3,
+        true,
+        true,
+        false,
+    )
+    .unwrap();
     assert_eq!(
         [true, true, true],
         [
@@ -450,6 +588,27 @@ fn test_pt_x64_xn() {
     );
 }
 
+#[test]
+fn test_pt_x64_parallel_repeated_frame() {
+    let mut mem = [0u64; 512 * 16]; // 16 physical pages
+    mem[0] = 0x1003; // PML4E
+    mem[1] = 0x2003;
+    mem[512 * 1 + 511] = 0x40000083; // 1 GiB page
+
+    // The same frame twice at the start of the next subtree
+    mem[512 * 2] = 0x80000083;
+    mem[512 * 2 + 1] = 0x80000083;
+    let mem_as_u8 = mem.as_byte_slice();
+    let mut memory_view = MemoryViewFromArray::from(&mem_as_u8);
+    let expected =
+        x86::collect_pages(X86Flavour::X64, &mut memory_view, 0, true, true, false).unwrap();
+    assert_eq!(1, expected.len());
+    assert_eq!(3 * 1024 * 1024 * 1024, expected[0].get_extent());
+    let result =
+        x86::collect_pages_parallel(X86Flavour::X64, &memory_view, 0, true, true, false).unwrap();
+    compare_page_vectors(&expected, &result);
+}
+
 #[test]
 fn test_pt_x64_pse() {
     // PAE and PSE must not matter when with x64
@@ -462,7 +621,8 @@ fn test_pt_x64_pse() {
         let mem_as_u8 = mem.as_byte_slice();
         let mut memory_view = MemoryViewFromArray::from(&mem_as_u8);
         let cr3 = 0u64;
-        let result = x86::collect_pages(X86Flavour::X64, &mut memory_view, cr3, pse, pae).unwrap();
+        let result =
+            x86::collect_pages(X86Flavour::X64, &mut memory_view, cr3, pse, pae, false).unwrap();
         compare_page_vectors(
             &vec![
                 create_page(
@@ -487,6 +647,48 @@ fn test_pt_x64_pse() {
     }
 }
 
+#[test]
+fn test_pt_x64_parallel() {
+    let mut mem = [0u64; 512 * 16]; // 16 physical pages
+    mem[0] = 0x1003; // PML4E
+    mem[1] = 0x2003;
+    mem[2] = 0x3003;
+    mem[3] = 0x4003;
+    mem[512 * 1 + 511] = 0x40000083; // 1 GiB page
+
+    // Physically contiguous to the previous subtree
+    mem[512 * 2] = 0x80000083;
+    // Contained in the range coalesced over the subtree boundary
+    mem[512 * 2 + 1] = 0x40000083;
+    mem[512 * 2 + 511] = 0x100000083;
+    // Not physically contiguous to the previous subtree
+    mem[512 * 3] = 0x200000083;
+    mem[512 * 4] = 0x8000000000000083; // NX
+    mem[512 * 4 + 1] = 0x5007; // PDE
+    mem[512 * 5] = 0x6087; // 2 MiB user page
+    let mem_as_u8 = mem.as_byte_slice();
+    let mut memory_view = MemoryViewFromArray::from(&mem_as_u8);
+    let cr3 = 0u64;
+    let expected =
+        x86::collect_pages(X86Flavour::X64, &mut memory_view, cr3, true, true, false).unwrap();
+    assert_eq!(expected.len(), 4);
+    assert_eq!(expected[0].get_phys_ranges().len(), 2);
+    assert_eq!(expected[1].get_phys_ranges().len(), 2);
+    let result =
+        x86::collect_pages_parallel(X86Flavour::X64, &memory_view, cr3, true, true, false).unwrap();
+    compare_page_vectors(&expected, &result);
+
+    assert!(x86::collect_pages_parallel(
+        X86Flavour::X64,
+        &memory_view,
+        0x1000000,
+        true,
+        true,
+        false
+    )
+    .is_err());
+}
+
 #[test]
 fn test_pt_x64_invalid_pages() {
     let mut mem = [0u64; 1024 * 16]; // 16 physical pages
@@ -500,8 +702,15 @@ fn test_pt_x64_invalid_pages() {
     let mem_as_u8 = mem.as_byte_slice();
     let mut memory_view = MemoryViewFromArray::from(&mem_as_u8);
     let cr3 = 0u64;
-    let result =
-        x86::collect_pages(x86::X86Flavour::X64, &mut memory_view, cr3, false, false).unwrap();
+    let result = x86::collect_pages(
+        x86::X86Flavour::X64,
+        &mut memory_view,
+        cr3,
+        false,
+        false,
+        false,
+    )
+    .unwrap();
     assert_eq!(
         vec![create_page(false, false, false, 0, 0x4000, 0x1000)],
         result
@@ -517,11 +726,108 @@ fn test_pt_x64_canonical_address() {
     let mem_as_u8 = mem.as_byte_slice();
     let mut memory_view = MemoryViewFromArray::from(&mem_as_u8);
     let cr3 = 0u64;
-    let result =
-        x86::collect_pages(x86::X86Flavour::X64, &mut memory_view, cr3, true, true).unwrap();
+    let result = x86::collect_pages(
+        x86::X86Flavour::X64,
+        &mut memory_view,
+        cr3,
+        true,
+        true,
+        false,
+    )
+    .unwrap();
     assert_eq!(0xffff810000000_000, result[0].get_va());
 }
 
+#[test]
+fn test_pt_x64_la57_single_page() {
+    let mut mem = [0u64; 512 * 16]; // 16 physical pages
+    mem[0] = 0x1001; // PML5E
+    mem[512 * 1] = 0x2001; // PML4E
+    mem[512 * 2] = 0x3001; // PDPE
+    mem[512 * 3] = 0x4001; // PDE
+    mem[512 * 4 + 1] = 0x5001; // PTE
+    let mem_as_u8 = mem.as_byte_slice();
+    let mut memory_view = MemoryViewFromArray::from(&mem_as_u8);
+    let cr3 = 0u64;
+    let result = x86::collect_pages(
+        x86::X86Flavour::X64,
+        &mut memory_view,
+        cr3,
+        false,
+        false,
+        true,
+    )
+    .unwrap();
+    assert_eq!(
+        vec![create_page(false, false, false, 0x1000, 0x5000, 0x1000)],
+        result
+    );
+}
+
+#[test]
+fn test_pt_x64_la57_canonical_address() {
+    let mut mem = [0u64; 512 * 16]; // 16 physical pages
+    mem[256] = 0x1001; // PML5E or PML4E
+    mem[512 * 1] = 0x2001; // PML4E or PDPE
+    mem[512 * 2] = 0x40000081; // PDPE or PDE
+    let mem_as_u8 = mem.as_byte_slice();
+    let mut memory_view = MemoryViewFromArray::from(&mem_as_u8);
+    let cr3 = 0u64;
+    let result = x86::collect_pages(
+        x86::X86Flavour::X64,
+        &mut memory_view,
+        cr3,
+        false,
+        false,
+        true,
+    )
+    .unwrap();
+    assert_eq!(
+        vec![create_page(
+            false,
+            false,
+            false,
+            0xff00000000000000,
+            0x40000000,
+            0x40000000
+        )],
+        result
+    );
+
+    // The same memory interpreted with four levels
+    let result = x86::collect_pages(
+        x86::X86Flavour::X64,
+        &mut memory_view,
+        cr3,
+        false,
+        false,
+        false,
+    )
+    .unwrap();
+    assert_eq!(
+        vec![create_page(
+            false,
+            false,
+            false,
+            0xffff800000000000,
+            0x40000000,
+            0x200000
+        )],
+        result
+    );
+
+    // LA57 has no meaning for x86-32
+    let result = x86::collect_pages(
+        x86::X86Flavour::X86,
+        &mut memory_view,
+        cr3,
+        true,
+        true,
+        true,
+    );
+    assert!(result.is_ok());
+}
+
 #[test]
 fn test_pt_gva_to_gpa() {
     let attr = PageAttributes {
@@ -568,3 +874,853 @@ fn test_pt_gva_to_gpa() {
     assert_eq!(Some(0x133200), range.gva_to_gpa(0x101200));
     assert_eq!(Some(0x4999), range.gva_to_gpa(0x102999));
 }
+
+fn create_riscv_page(
+    r: bool,
+    w: bool,
+    x: bool,
+    u: bool,
+    va: u64,
+    phys: u64,
+    extent: u64,
+) -> crate::pt::riscv::RiscvPageRange {
+    crate::pt::riscv::RiscvPageRange::new(
+        va,
+        extent,
+        crate::pt::riscv::RiscvPageAttributes {
+            readable: r,
+            writeable: w,
+            executable: x,
+            user: u,
+            global: false,
+            accessed: false,
+            dirty: false,
+        },
+        vec![PhysRange::new(phys, extent)],
+    )
+}
+
+#[test]
+fn test_pt_riscv_sv39_single_page() {
+    use crate::pt::riscv::{self, RiscvMode};
+
+    let mut mem = [0u64; 512 * 16]; // 16 physical pages
+    mem[0] = (1 << 10) | 0x1; // Pointer to the level 1 table
+    mem[512 * 1] = (2 << 10) | 0x1; // Pointer to the level 0 table
+    mem[512 * 2 + 3] = (5 << 10) | 0x1f; // V R W X U
+    let mem_as_u8 = mem.as_byte_slice();
+    let mut memory_view = MemoryViewFromArray::from(&mem_as_u8);
+    let result = riscv::collect_pages(RiscvMode::Sv39, &mut memory_view, 0).unwrap();
+    assert_eq!(
+        vec![create_riscv_page(
+            true, true, true, true, 0x3000, 0x5000, 0x1000
+        )],
+        result
+    );
+}
+
+#[test]
+fn test_pt_riscv_sv39_superpages() {
+    use crate::pt::riscv::{self, RiscvMode};
+
+    let mut mem = [0u64; 512 * 16]; // 16 physical pages
+    mem[1] = (0x40000 << 10) | 0x3; // 1 GiB page, R
+    mem[2] = (0x40001 << 10) | 0x3; // Misaligned 1 GiB page
+    mem[3] = (1 << 10) | 0x1; // Pointer to the level 1 table
+    mem[256] = (0x80000 << 10) | 0xb; // 1 GiB page in the upper half, R X
+    mem[512 * 1] = (0x200 << 10) | 0x7; // 2 MiB page, R W
+    mem[512 * 1 + 1] = (0x800 << 10) | 0x7; // 2 MiB page, R W, physically discontiguous
+    mem[512 * 1 + 2] = (0xa00 << 10) | 0x5; // W without R is reserved
+    let mem_as_u8 = mem.as_byte_slice();
+    let mut memory_view = MemoryViewFromArray::from(&mem_as_u8);
+    let result = riscv::collect_pages(RiscvMode::Sv39, &mut memory_view, 0).unwrap();
+    let mut coalesced = create_riscv_page(true, true, false, false, 0xc0000000, 0x200000, 0x400000);
+    coalesced.phys_ranges = vec![
+        PhysRange::new(0x200000, 0x200000),
+        PhysRange::new(0x800000, 0x200000),
+    ];
+    assert_eq!(
+        vec![
+            create_riscv_page(true, false, false, false, 0x40000000, 0x40000000, 0x40000000),
+            coalesced,
+            create_riscv_page(
+                true,
+                false,
+                true,
+                false,
+                0xffffffc000000000,
+                0x80000000,
+                0x40000000
+            ),
+        ],
+        result
+    );
+}
+
+#[test]
+fn test_pt_riscv_sv48_sv57_canonical_address() {
+    use crate::pt::riscv::{self, RiscvMode};
+
+    let mut mem = [0u64; 512 * 16]; // 16 physical pages
+    mem[256] = (0x8000000 << 10) | 0x3; // 512 GiB page in Sv48, R
+    let mem_as_u8 = mem.as_byte_slice();
+    let mut memory_view = MemoryViewFromArray::from(&mem_as_u8);
+    let result = riscv::collect_pages(RiscvMode::Sv48, &mut memory_view, 0).unwrap();
+    assert_eq!(1, result.len());
+    assert_eq!(0xffff800000000000, result[0].va);
+    assert_eq!(0x8000000000, result[0].extent);
+
+    // The same entry is a pointer to a 256 TiB page in Sv57 and must be aligned accordingly.
+    let result = riscv::collect_pages(RiscvMode::Sv57, &mut memory_view, 0).unwrap();
+    assert_eq!(Vec::<riscv::RiscvPageRange>::new(), result);
+
+    mem[256] = (0x10000000000 << 10) | 0x3;
+    let mem_as_u8 = mem.as_byte_slice();
+    let mut memory_view = MemoryViewFromArray::from(&mem_as_u8);
+    let result = riscv::collect_pages(RiscvMode::Sv57, &mut memory_view, 0).unwrap();
+    assert_eq!(1, result.len());
+    assert_eq!(0xff00000000000000, result[0].va);
+}
+
+#[test]
+fn test_pt_riscv_satp_and_global() {
+    use crate::pt::riscv::{self, RiscvMode};
+
+    assert_eq!(Some(RiscvMode::Sv39), RiscvMode::from_satp(8 << 60));
+    assert_eq!(Some(RiscvMode::Sv48), RiscvMode::from_satp(9 << 60));
+    assert_eq!(Some(RiscvMode::Sv57), RiscvMode::from_satp(10 << 60));
+    assert_eq!(None, RiscvMode::from_satp(0));
+
+    let mut mem = [0u64; 512 * 16]; // 16 physical pages
+    mem[512 * 2] = (3 << 10) | 0x21; // Global pointer to the level 1 table
+    mem[512 * 3] = (0x200 << 10) | 0x3;
+    let mem_as_u8 = mem.as_byte_slice();
+    let mut memory_view = MemoryViewFromArray::from(&mem_as_u8);
+    let satp = (8u64 << 60) | (0x1234u64 << 44) | 2;
+    let result = riscv::collect_pages_from_satp(&mut memory_view, satp).unwrap();
+    assert_eq!(1, result.len());
+    assert_eq!(true, result[0].attr.global);
+    assert!(riscv::collect_pages_from_satp(&mut memory_view, 2).is_err());
+}
+
+fn create_arm_page(
+    permission_bits: u8,
+    xn: bool,
+    pxn: bool,
+    va: u64,
+    phys: u64,
+    extent: u64,
+) -> crate::pt::arm::ArmPageRange {
+    crate::pt::arm::ArmPageRange {
+        va: va,
+        extent: extent,
+        phys_ranges: vec![PhysRange::new(phys, extent)],
+        attr: crate::pt::arm::ArmPageAttributes {
+            xn: xn,
+            pxn: pxn,
+            permission_bits: permission_bits,
+            ..Default::default()
+        },
+    }
+}
+
+#[test]
+fn test_pt_arm32_short_descriptor() {
+    use crate::pt::arm::{
+        self, ArmContext, ArmFlavour, Granularity, PERMISSION_BITS_PL1_RW_PL0_RO,
+    };
+
+    let mut mem = vec![0u32; 1024 * 8]; // 8 physical pages
+    mem[0] = 0x4000 | 0x1; // Page table in domain 0
+    mem[1] = 0x100000 | (0b10 << 10) | 0x2; // Section, AP=010
+    mem[2] = 0x200000 | (0b11 << 10) | (1 << 5) | 0x2; // Section in domain 1, AP=011
+    for i in 16..32 {
+        // Supersection with PA[35:32] = 1, AP=011
+        mem[i] = 0x1000000 | (1 << 20) | (1 << 18) | (0b11 << 10) | 0x2;
+    }
+    mem[4096] = 0x10000 | (0b11 << 4) | 0x2; // Small page, AP=011
+    mem[4096 + 1] = 0x11000 | (0b01 << 4) | 0x3; // Small page, AP=001, XN
+    for i in 16..32 {
+        mem[4096 + i] = 0x20000 | (1 << 9) | (0b11 << 4) | 0x1; // Large page, AP=111
+    }
+    let mem_as_u8 = mem.as_byte_slice();
+    let mut memory_view = MemoryViewFromArray::from(&mem_as_u8);
+
+    // Domain 1 has no access
+    let mut context = ArmContext::new(ArmFlavour::Arm32, Granularity::Pt4k, 32, 0).unwrap();
+    context.set_dacr(0x1);
+    let result = arm::collect_pages(&context, &mut memory_view, 0).unwrap();
+    let accessed = |mut page: arm::ArmPageRange| {
+        // Short descriptors have no access flag
+        page.attr.accessed = true;
+        page
+    };
+    assert_eq!(
+        vec![
+            accessed(create_arm_page(0b01, false, false, 0x0, 0x10000, 0x1000)),
+            accessed(create_arm_page(0b00, true, false, 0x1000, 0x11000, 0x1000)),
+            accessed(create_arm_page(
+                0b11, false, false, 0x10000, 0x20000, 0x10000
+            )),
+            accessed(create_arm_page(
+                PERMISSION_BITS_PL1_RW_PL0_RO,
+                false,
+                false,
+                0x100000,
+                0x100000,
+                0x100000
+            )),
+            accessed(create_arm_page(
+                0b01,
+                false,
+                false,
+                0x1000000,
+                0x101000000,
+                0x1000000
+            )),
+        ],
+        result
+    );
+    assert_eq!(true, result[3].is_user_readable());
+    assert_eq!(false, result[3].is_user_writeable());
+    assert_eq!(true, result[3].is_kernel_writeable());
+
+    // Domain 1 is a manager, thus the section is accessible regardless of AP
+    context.set_dacr(0xd);
+    let result = arm::collect_pages(&context, &mut memory_view, 0).unwrap();
+    assert_eq!(
+        accessed(create_arm_page(
+            0b01, false, false, 0x200000, 0x200000, 0x100000
+        )),
+        result[4]
+    );
+
+    // With TTBCR.N = 1, TTBR0 covers only the bottom half
+    let context = ArmContext::new(ArmFlavour::Arm32, Granularity::Pt4k, 31, 1).unwrap();
+    let result = arm::collect_pages(&context, &mut memory_view, 0).unwrap();
+    assert_eq!(Vec::<arm::ArmPageRange>::new(), result);
+
+    // TTBCR.N can't select fewer than 25 bits
+    assert!(ArmContext::new(ArmFlavour::Arm32, Granularity::Pt4k, 19, 0).is_err());
+}
+
+#[test]
+fn test_pt_arm32_lpae() {
+    use crate::pt::arm::{self, ArmContext, ArmFlavour, Granularity};
+
+    let mut mem = vec![0u64; 512 * 8]; // 8 physical pages
+    mem[0] = 0x1000 | 0x3; // L1 table
+    mem[2] = 0x100000000 | (0b11 << 6) | 0x1; // 1 GiB block, 40-bit output address
+    mem[4] = 0x40000000 | 0x1; // Outside of the 4-entry L1 table
+    mem[512] = 0x400000 | (1 << 54) | 0x1; // 2 MiB block, XN
+    mem[512 + 1] = 0x2000 | 0x3; // L3 table
+    mem[1024 + 3] = 0x5000 | (0b01 << 6) | 0x3; // 4K page
+    let mem_as_u8 = mem.as_byte_slice();
+    let mut memory_view = MemoryViewFromArray::from(&mem_as_u8);
+    let context = ArmContext::new(ArmFlavour::Arm32Lpae, Granularity::Pt4k, 32, 0).unwrap();
+    let result = arm::collect_pages(&context, &mut memory_view, 0).unwrap();
+    assert_eq!(
+        vec![
+            create_arm_page(0b00, true, false, 0x0, 0x400000, 0x200000),
+            create_arm_page(0b01, false, false, 0x203000, 0x5000, 0x1000),
+            create_arm_page(0b11, false, false, 0x80000000, 0x100000000, 0x40000000),
+        ],
+        result
+    );
+}
+
+#[test]
+fn test_pt_aarch64_hierarchical_permissions() {
+    use crate::pt::arm::{self, ArmContext, ArmFlavour, Granularity};
+
+    let mut mem = vec![0u64; 512 * 8]; // 8 physical pages
+    mem[0] = 0x1000 | (0b10 << 61) | (1 << 60) | 0x3; // APTable: no writes, UXNTable
+    mem[512] = 0x2000 | (0b01 << 61) | 0x3; // APTable: no EL0 access
+    mem[512 + 1] = 0x400000 | (0b01 << 6) | 0x1; // 2 MiB block, AP: RW at EL1 and EL0
+    mem[1024] = 0x5000 | (0b01 << 6) | 0x3; // 4K page, AP: RW at EL1 and EL0
+    let mem_as_u8 = mem.as_byte_slice();
+    let mut memory_view = MemoryViewFromArray::from(&mem_as_u8);
+
+    let mut context = ArmContext::new(ArmFlavour::Arm64, Granularity::Pt4k, 39, 0).unwrap();
+    let result = arm::collect_pages(&context, &mut memory_view, 0).unwrap();
+    assert_eq!(
+        vec![
+            create_arm_page(0b10, true, false, 0x0, 0x5000, 0x1000),
+            create_arm_page(0b11, true, false, 0x200000, 0x400000, 0x200000),
+        ],
+        result
+    );
+    assert_eq!(false, result[0].is_user_readable());
+    assert_eq!(false, result[0].is_kernel_writeable());
+    assert_eq!(true, result[1].is_user_readable());
+    assert_eq!(false, result[1].is_user_writeable());
+
+    context.set_hierarchical_permissions(false);
+    let result = arm::collect_pages(&context, &mut memory_view, 0).unwrap();
+    assert_eq!(
+        vec![
+            create_arm_page(0b01, false, false, 0x0, 0x5000, 0x1000),
+            create_arm_page(0b01, false, false, 0x200000, 0x400000, 0x200000),
+        ],
+        result
+    );
+}
+
+#[test]
+fn test_pt_aarch64_parallel() {
+    use crate::pt::arm::{self, ArmContext, ArmFlavour, Granularity};
+
+    let mut mem = vec![0u64; 512 * 8]; // 8 physical pages
+    mem[0] = 0x1000 | 0x3; // L0 table descriptors
+    mem[1] = 0x2000 | 0x3;
+    mem[2] = 0x3000 | 0x3;
+    mem[512 + 511] = 0x40000000 | 0x1; // 1 GiB block
+
+    // The same frame twice at the start of the next subtree
+    mem[1024] = 0x80000000 | 0x1;
+    mem[1024 + 1] = 0x80000000 | 0x1;
+    mem[1536] = 0x4000 | 0x3; // L1 table descriptor
+    mem[2048] = 0x5000 | 0x3; // L2 table descriptor
+    mem[2560] = 0x6000 | (0b01 << 6) | 0x3; // 4K page, AP: RW at EL1 and EL0
+    let mem_as_u8 = mem.as_byte_slice();
+    let mut memory_view = MemoryViewFromArray::from(&mem_as_u8);
+
+    let context = ArmContext::new(ArmFlavour::Arm64, Granularity::Pt4k, 48, 0).unwrap();
+    let expected = arm::collect_pages(&context, &mut memory_view, 0).unwrap();
+    assert_eq!(2, expected.len());
+    assert_eq!(3 * 1024 * 1024 * 1024, expected[0].extent);
+    let result = arm::collect_pages_parallel(&context, &memory_view, 0).unwrap();
+    assert_eq!(expected, result);
+
+    let context = ArmContext::new(ArmFlavour::Arm32, Granularity::Pt4k, 32, 0).unwrap();
+    assert_eq!(
+        arm::collect_pages(&context, &mut memory_view, 0).unwrap(),
+        arm::collect_pages_parallel(&context, &memory_view, 0).unwrap()
+    );
+}
+
+#[test]
+fn test_pt_aarch64_leaf_attributes() {
+    use crate::pt::arm::{self, mair_attr_to_string, ArmContext, ArmFlavour, Granularity};
+
+    let mut mem = vec![0u64; 512 * 8]; // 8 physical pages
+    mem[0] = 0x1000 | 0x3;
+    mem[512] = 0x2000 | 0x3;
+    // AttrIndx=1, ISH, AF, nG, GP, DBM, contiguous
+    mem[1024] = 0x5000
+        | (1 << 2)
+        | (0b11 << 8)
+        | (1 << 10)
+        | (1 << 11)
+        | (1 << 50)
+        | (1 << 51)
+        | (1 << 52)
+        | 0x3;
+    mem[1024 + 1] = 0x6000 | (1 << 10) | 0x3; // AttrIndx=0
+    mem[1024 + 2] = 0x7000 | 0x3; // Only differs in AF, so it is merged into the previous page
+    let mem_as_u8 = mem.as_byte_slice();
+    let mut memory_view = MemoryViewFromArray::from(&mem_as_u8);
+
+    let mut context = ArmContext::new(ArmFlavour::Arm64, Granularity::Pt4k, 39, 0).unwrap();
+    let result = arm::collect_pages(&context, &mut memory_view, 0).unwrap();
+    assert_eq!(2, result.len());
+    assert_eq!(0x2000, result[1].extent);
+    let attr = &result[0].attr;
+    assert_eq!(1, attr.attr_index);
+    assert_eq!("ISH", attr.get_shareability_str());
+    assert_eq!(
+        [true, true, true, true, true],
+        [
+            attr.accessed,
+            attr.not_global,
+            attr.guarded,
+            attr.dbm,
+            attr.contiguous
+        ]
+    );
+    assert_eq!(None, attr.get_memory_type());
+
+    context.set_mair(0xff00);
+    let result = arm::collect_pages(&context, &mut memory_view, 0).unwrap();
+    assert_eq!(
+        Some(String::from("Normal-WB")),
+        result[0].attr.get_memory_type()
+    );
+    assert_eq!(Some(false), result[0].attr.is_device_memory());
+    assert_eq!(
+        Some(String::from("Device-nGnRnE")),
+        result[1].attr.get_memory_type()
+    );
+    assert_eq!(Some(true), result[1].attr.is_device_memory());
+
+    assert_eq!("Device-nGnRE", mair_attr_to_string(0x04));
+    assert_eq!("Normal-NC", mair_attr_to_string(0x44));
+    assert_eq!("Normal-IWB-ONC", mair_attr_to_string(0x4f));
+    assert_eq!("Normal-WT", mair_attr_to_string(0xbb));
+    assert_eq!("Normal-Tagged", mair_attr_to_string(0xf0));
+}
+
+#[test]
+fn test_pt_ept() {
+    use crate::pt::ept::{self, EptContext};
+
+    let mut mem = [0u64; 512 * 16]; // 16 physical pages
+    mem[0] = 0x1000 | (1 << 10) | 0x7; // PML4E
+    mem[512] = 0x2000 | (1 << 10) | 0x7; // PDPTE
+    mem[512 + 1] = 0x40000000 | (6 << 3) | (1 << 7) | 0x7; // 1 GiB page, WB
+    mem[512 * 2] = 0x3000 | (1 << 10) | 0x7; // PDE
+    mem[512 * 3] = 0x5000 | (6 << 3) | (1 << 8) | 0x1; // R, WB, accessed
+    mem[512 * 3 + 1] = 0x6000 | 0x2; // W without R is a misconfiguration
+    mem[512 * 3 + 2] = 0x7000 | (1 << 10) | 0x4; // X, user execute
+    mem[512 * 3 + 3] = 0x8000 | (1 << 10); // User execute only
+    let mem_as_u8 = mem.as_byte_slice();
+    let mut memory_view = MemoryViewFromArray::from(&mem_as_u8);
+
+    let eptp = (3 << 3) | 6;
+    let result = ept::collect_pages_from_eptp(&mut memory_view, eptp, false).unwrap();
+    assert_eq!(
+        vec![
+            (0x0, 0x5000, 0x1000),
+            (0x2000, 0x7000, 0x1000),
+            (0x40000000, 0x40000000, 0x40000000)
+        ],
+        result
+            .iter()
+            .map(|r| (r.gpa, r.phys_ranges[0].phys_base, r.extent))
+            .collect::<Vec<(u64, u64, u64)>>()
+    );
+    assert_eq!(
+        [true, false, false, false, true],
+        [
+            result[0].attr.readable,
+            result[0].attr.writeable,
+            result[0].attr.executable,
+            result[0].attr.user_executable,
+            result[0].attr.accessed
+        ]
+    );
+    assert_eq!("WB", result[0].attr.get_memory_type_str());
+    assert_eq!("UC", result[1].attr.get_memory_type_str());
+    assert_eq!(true, result[1].attr.user_executable);
+
+    let result = ept::collect_pages(&EptContext::new(4, true), &mut memory_view, 0).unwrap();
+    assert_eq!(4, result.len());
+    assert_eq!(0x3000, result[2].gpa);
+    assert_eq!(
+        [false, true],
+        [result[2].attr.executable, result[2].attr.user_executable]
+    );
+
+    assert!(ept::collect_pages(&EptContext::new(3, false), &mut memory_view, 0).is_err());
+}
+
+#[test]
+fn test_pt_ept_read_only_pdpte() {
+    use crate::pt::ept::{self, EptContext};
+
+    let mut mem = [0u64; 512 * 16]; // 16 physical pages
+    mem[0] = 0x1000 | 0x7; // PML4E
+    mem[512] = 0x2000 | 0x1; // Read-only PDPTE
+    mem[512 * 2] = 0x3000 | 0x7; // PDE
+    mem[512 * 3] = 0x5000 | (6 << 3) | 0x7; // RWX, WB
+    mem[512 * 2 + 1] = 0x200000 | (6 << 3) | (1 << 7) | 0x3; // 2 MiB page, RW
+    let mem_as_u8 = mem.as_byte_slice();
+    let mut memory_view = MemoryViewFromArray::from(&mem_as_u8);
+
+    let result = ept::collect_pages(&EptContext::new(4, false), &mut memory_view, 0).unwrap();
+    assert_eq!(
+        vec![(0x0, 0x1000), (0x200000, 0x200000)],
+        result
+            .iter()
+            .map(|r| (r.gpa, r.extent))
+            .collect::<Vec<(u64, u64)>>()
+    );
+    for range in result.iter() {
+        assert_eq!(
+            [true, false, false, false],
+            [
+                range.attr.readable,
+                range.attr.writeable,
+                range.attr.executable,
+                range.attr.user_executable
+            ]
+        );
+    }
+}
+
+#[test]
+fn test_pt_npt() {
+    let mut mem = [0u64; 512 * 16]; // 16 physical pages
+    mem[256] = 0x1007; // PML4E
+    mem[512 * 1] = 0x40000087; // PDPE
+    let mem_as_u8 = mem.as_byte_slice();
+    let mut memory_view = MemoryViewFromArray::from(&mem_as_u8);
+    let result = x86::collect_npt_pages(&mut memory_view, 0, false).unwrap();
+    assert_eq!(
+        vec![create_page(
+            true,
+            true,
+            false,
+            0x800000000000,
+            0x40000000,
+            0x40000000
+        )],
+        result
+    );
+}
+
+#[test]
+fn test_pt_aarch64_stage2() {
+    use crate::pt::arm::{self, ArmPageAttributes, ArmPageRange};
+
+    let mut mem = vec![0u64; 512 * 4]; // 4 physical pages
+
+    // 40-bit IPA starting at level 1, thus two concatenated root tables.
+    mem[0] = 0x40000000 | (0b1111 << 2) | (0b11 << 6) | (0b11 << 8) | (1 << 10) | 0b01;
+    mem[512] = 0x2000 | 0b11;
+    mem[1024] = 0x3000 | 0b11;
+    mem[1536] = 0x90000000 | (0b0001 << 2) | (0b01 << 6) | (1 << 10) | (0b10 << 53) | 0b11;
+    mem[1537] = 0x90001000 | (0b0101 << 2) | (0b10 << 6) | (1 << 10) | (0b01 << 53) | 0b11;
+    let mem_as_u8 = mem.as_byte_slice();
+    let mut memory_view = MemoryViewFromArray::from(&mem_as_u8);
+
+    // T0SZ = 24, SL0 = 1, TG0 = 4K, VMID = 5
+    let vtcr = 24 | (0b01 << 6);
+    let vttbr = 5 << 48;
+    let result = arm::collect_stage2_pages_from_vttbr(&mut memory_view, vttbr, vtcr).unwrap();
+    let create_page = |va: u64, phys: u64, extent: u64, attr: ArmPageAttributes| ArmPageRange {
+        va: va,
+        extent: extent,
+        phys_ranges: vec![PhysRange::new(phys, extent)],
+        attr: ArmPageAttributes {
+            accessed: true,
+            stage2: true,
+            ..attr
+        },
+    };
+    assert_eq!(
+        vec![
+            create_page(
+                0x0,
+                0x40000000,
+                0x40000000,
+                ArmPageAttributes {
+                    permission_bits: 0b11,
+                    attr_index: 0b1111,
+                    mair_attr: Some(0xcc),
+                    shareability: 0b11,
+                    ..Default::default()
+                }
+            ),
+            create_page(
+                0x80_0000_0000,
+                0x90000000,
+                0x1000,
+                ArmPageAttributes {
+                    xn: true,
+                    pxn: true,
+                    permission_bits: 0b01,
+                    attr_index: 0b0001,
+                    mair_attr: Some(0x04),
+                    ..Default::default()
+                }
+            ),
+            create_page(
+                0x80_0000_1000,
+                0x90001000,
+                0x1000,
+                ArmPageAttributes {
+                    pxn: true,
+                    permission_bits: 0b10,
+                    attr_index: 0b0101,
+                    mair_attr: Some(0x44),
+                    ..Default::default()
+                }
+            ),
+        ],
+        result
+    );
+    assert_eq!(
+        Some(String::from("Normal-WB")),
+        result[0].attr.get_memory_type()
+    );
+    assert_eq!(Some(true), result[1].attr.is_device_memory());
+    assert_eq!(true, result[1].is_kernel_readable());
+    assert_eq!(false, result[1].is_kernel_writeable());
+    assert_eq!(false, result[2].is_user_readable());
+    assert_eq!(true, result[2].is_user_writeable());
+    assert_eq!(true, result[2].is_user_executable());
+
+    // A 40-bit IPA cannot start at level 2 since it would need more than 16 concatenated tables.
+    let vtcr = 24 | (0b00 << 6);
+    assert!(arm::collect_stage2_pages_from_vttbr(&mut memory_view, vttbr, vtcr).is_err());
+}
+
+#[test]
+fn test_pt_nested_x86_over_ept() {
+    use crate::pt::ept;
+    use crate::pt::nested::{self, NestedPageAttributes, NestedPageRange};
+    use crate::pt::x86::{self, X86Flavour};
+
+    let mut mem = vec![0u64; 512 * 0x21]; // 0x21 host physical pages
+    let page = |hpa: usize| hpa / 8;
+    // EPT maps the first 8 guest physical pages, page 5 is relocated and page 6 is read-only.
+    mem[page(0x0)] = 0x1000 | 0x7;
+    mem[page(0x1000)] = 0x2000 | 0x7;
+    mem[page(0x2000)] = 0x3000 | 0x7;
+    for i in 0..8 {
+        mem[page(0x3000) + i] = (0x8000 + (i as u64) * 0x1000) | (6 << 3) | 0x7;
+    }
+    mem[page(0x3000) + 5] = 0x20000 | (6 << 3) | 0x7;
+    mem[page(0x3000) + 6] = 0xe000 | (6 << 3) | 0x1;
+    // Guest page table at GPA 0x0, i.e. HPA 0x8000
+    mem[page(0x8000)] = 0x1000 | 0x7;
+    mem[page(0x9000)] = 0x2000 | 0x7;
+    mem[page(0xa000)] = 0x3000 | 0x7;
+    mem[page(0xb000)] = 0x4000 | 0x7;
+    mem[page(0xb000) + 1] = 0x5000 | 0x7;
+    mem[page(0xb000) + 2] = 0x6000 | 0x7;
+    mem[page(0xb000) + 3] = 0x9000 | 0x7; // Not mapped by EPT
+    let mem_as_u8 = mem.as_byte_slice();
+    let mut memory_view = MemoryViewFromArray::from(&mem_as_u8);
+
+    let eptp = 0x0 | (3 << 3) | 6;
+    let stage2 = ept::collect_pages_from_eptp(&mut memory_view, eptp, false).unwrap();
+    let result = nested::collect_nested_pages(&mut memory_view, &stage2, |guest_memory| {
+        x86::collect_pages(X86Flavour::X64, guest_memory, 0x0, true, true, false)
+    })
+    .unwrap();
+
+    let read_only = NestedPageAttributes {
+        user_readable: true,
+        user_writeable: false,
+        user_executable: false,
+        kernel_readable: true,
+        kernel_writeable: false,
+        kernel_executable: false,
+    };
+    assert_eq!(
+        vec![
+            NestedPageRange {
+                gva: 0x0,
+                extent: 0x2000,
+                gpa: 0x4000,
+                phys_ranges: vec![
+                    PhysRange::new(0xc000, 0x1000),
+                    PhysRange::new(0x20000, 0x1000)
+                ],
+                attr: NestedPageAttributes {
+                    user_writeable: true,
+                    user_executable: true,
+                    kernel_writeable: true,
+                    kernel_executable: true,
+                    ..read_only.clone()
+                },
+            },
+            NestedPageRange {
+                gva: 0x2000,
+                extent: 0x1000,
+                gpa: 0x6000,
+                phys_ranges: vec![PhysRange::new(0xe000, 0x1000)],
+                attr: read_only,
+            },
+        ],
+        result
+    );
+}
+
+#[test]
+fn test_pt_x64_translate() {
+    use crate::pt::common::WalkFaultReason;
+    use crate::pt::x86::{LevelType, X86TranslatedAddress, X86WalkEntry, X86WalkFault};
+
+    let mut mem = [0u64; 512 * 4]; // 4 physical pages
+    mem[511] = 0x1000 | 0x7; // PML4E
+    mem[512 + 510] = 0x2000 | 0x3; // PDPE, supervisor only
+    mem[1024 + 9] = 0x40000000 | (1 << 63) | 0x80 | 0x1; // 2M page, read-only and NX
+    let mem_as_u8 = mem.as_byte_slice();
+    let mut memory_view = MemoryViewFromArray::from(&mem_as_u8);
+
+    let result = x86::translate(
+        X86Flavour::X64,
+        &mut memory_view,
+        0,
+        true,
+        true,
+        false,
+        0xffffffff81234567,
+    )
+    .unwrap();
+    assert_eq!(
+        vec![
+            (
+                LevelType::PML4,
+                511,
+                0x0,
+                X86WalkEntry::Table {
+                    next_table_pa: 0x1000
+                }
+            ),
+            (
+                LevelType::PDP,
+                510,
+                0x1000,
+                X86WalkEntry::Table {
+                    next_table_pa: 0x2000
+                }
+            ),
+            (
+                LevelType::PD,
+                9,
+                0x2000,
+                X86WalkEntry::Leaf {
+                    page_pa: 0x40000000,
+                    page_size: 0x200000
+                }
+            ),
+        ],
+        result
+            .steps
+            .iter()
+            .map(|step| (step.level, step.index, step.table_pa, step.entry.clone()))
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(mem[1024 + 9], result.steps[2].raw_entry);
+    assert_eq!(
+        Ok(X86TranslatedAddress {
+            pa: 0x40034567,
+            page_size: 0x200000,
+            attributes: PageAttributes {
+                writeable: false,
+                user: false,
+                pwt: false,
+                pcd: false,
+                accessed: false,
+                dirty: false,
+                global: false,
+                pat: false,
+                nx: true,
+            },
+        }),
+        result.result
+    );
+
+    let result = x86::translate(
+        X86Flavour::X64,
+        &mut memory_view,
+        0,
+        true,
+        true,
+        false,
+        0xffffffff80000000,
+    )
+    .unwrap();
+    assert_eq!(3, result.steps.len());
+    assert_eq!(
+        Err(X86WalkFault {
+            level: LevelType::PD,
+            reason: WalkFaultReason::NotPresent
+        }),
+        result.result
+    );
+
+    let result = x86::translate(
+        X86Flavour::X64,
+        &mut memory_view,
+        0,
+        true,
+        true,
+        false,
+        0x0000800000000000,
+    )
+    .unwrap();
+    assert_eq!(0, result.steps.len());
+    assert_eq!(
+        Err(X86WalkFault {
+            level: LevelType::PML4,
+            reason: WalkFaultReason::AddressOutOfRange
+        }),
+        result.result
+    );
+
+    assert!(x86::translate(
+        X86Flavour::X64,
+        &mut memory_view,
+        0x1000000,
+        true,
+        true,
+        false,
+        0xffffffff81234567,
+    )
+    .is_err());
+}
+
+#[test]
+fn test_pt_aarch64_translate() {
+    use crate::pt::arm::{
+        self, ArmContext, ArmFlavour, ArmPageAttributes, ArmTranslatedAddress, ArmWalkFault,
+        Granularity,
+    };
+    use crate::pt::common::WalkFaultReason;
+
+    let mut mem = vec![0u64; 512 * 4]; // 4 physical pages
+    mem[0] = 0x1000 | (0b01 << 61) | 0b11; // APTable[0] removes EL0 access
+    mem[512] = 0x2000 | 0b11;
+    mem[1024 + 1] = 0x3000 | 0b11;
+    mem[1536 + 1] = 0x80000000 | (1 << 10) | (0b01 << 6) | 0b11;
+    mem[1536 + 2] = 0x81000000 | 0b01; // Reserved at the last level
+    let mem_as_u8 = mem.as_byte_slice();
+    let mut memory_view = MemoryViewFromArray::from(&mem_as_u8);
+    let context = ArmContext::new(ArmFlavour::Arm64, Granularity::Pt4k, 48, 1).unwrap();
+    assert!(ArmContext::new(ArmFlavour::Arm64, Granularity::Pt4k, 64, 1).is_err());
+
+    let result = arm::translate(&context, &mut memory_view, 0, 0xffff000000201234).unwrap();
+    assert_eq!(
+        vec![(0, 0, 0x0), (1, 0, 0x1000), (2, 1, 0x2000), (3, 1, 0x3000)],
+        result
+            .steps
+            .iter()
+            .map(|step| (step.level, step.index, step.table_pa))
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(
+        Ok(ArmTranslatedAddress {
+            pa: 0x80000234,
+            page_size: 0x1000,
+            attr: ArmPageAttributes {
+                permission_bits: 0b00,
+                accessed: true,
+                ..Default::default()
+            },
+        }),
+        result.result
+    );
+
+    let result = arm::translate(&context, &mut memory_view, 0, 0xffff000000401234).unwrap();
+    assert_eq!(
+        Err(ArmWalkFault {
+            level: 2,
+            reason: WalkFaultReason::NotPresent
+        }),
+        result.result
+    );
+
+    let result = arm::translate(&context, &mut memory_view, 0, 0xffff000000202234).unwrap();
+    assert_eq!(
+        Err(ArmWalkFault {
+            level: 3,
+            reason: WalkFaultReason::ReservedEncoding
+        }),
+        result.result
+    );
+
+    let result = arm::translate(&context, &mut memory_view, 0, 0x1234).unwrap();
+    assert_eq!(0, result.steps.len());
+    assert_eq!(
+        Err(ArmWalkFault {
+            level: 0,
+            reason: WalkFaultReason::AddressOutOfRange
+        }),
+        result.result
+    );
+}
@@ -299,3 +299,1241 @@ fn search_for_bytes_aligned() {
             .collect::<Vec<u64>>()
     );
 }
+
+#[test]
+fn search_for_bytes_parallel() {
+    use crate::search::bytes_search::{search_memory_generic, search_pattern_parallel_chunked};
+    use memchr::memmem;
+
+    let attr = PageAttributes {
+        accessed: false,
+        dirty: false,
+        writeable: false,
+        user: false,
+        pwt: false,
+        pcd: false,
+        pat: false,
+        global: false,
+        nx: true,
+    };
+
+    let mut mem = vec![0u8; 0x20000];
+    let ranges = vec![
+        X86PageRange::new(
+            0xa00000,
+            0x2000,
+            attr.clone(),
+            vec![
+                PhysRange::new(0x1000, 0x1000),
+                PhysRange::new(0x3000, 0x1000),
+            ],
+        ),
+        X86PageRange::new(
+            0xb00000,
+            0x1000,
+            attr.clone(),
+            vec![PhysRange::new(0x9000, 0x1000)],
+        ),
+    ];
+
+    let needle = "KeyWord".as_byte_slice();
+    let mut copy_needle_to = |off| mem[off..off + needle.len()].copy_from_slice(&needle);
+    copy_needle_to(0x1000);
+    copy_needle_to(0x13fe); // Crosses a chunk boundary
+    copy_needle_to(0x1f00);
+    copy_needle_to(0x3500);
+    copy_needle_to(0x9400);
+    copy_needle_to(0x9ff9); // Ends at the end of the range
+    let mem_as_u8 = mem.as_byte_slice();
+    let mut memory_view = MemoryViewFromArray::from(&mem_as_u8);
+    for max_num_occurrences in 0..8 {
+        let expected = search_memory_generic(
+            &needle,
+            &ranges,
+            &mut memory_view,
+            None,
+            max_num_occurrences,
+        );
+        for chunk_size in [0x400, 0x1000, 0x10000] {
+            let result = search_pattern_parallel_chunked(
+                &memmem::Finder::new(&needle),
+                &ranges,
+                &memory_view,
+                None,
+                max_num_occurrences,
+                chunk_size,
+            );
+            assert_eq!(
+                expected
+                    .get_results()
+                    .iter()
+                    .map(|x| (x.va, x.range_index))
+                    .collect::<Vec<_>>(),
+                result
+                    .get_results()
+                    .iter()
+                    .map(|x| (x.va, x.range_index))
+                    .collect::<Vec<_>>()
+            );
+        }
+    }
+    let result = search_pattern_parallel_chunked(
+        &memmem::Finder::new(&needle),
+        &ranges,
+        &memory_view,
+        None,
+        100,
+        0x400,
+    );
+    assert_eq!(
+        vec![0xa00000, 0xa003fe, 0xa00f00, 0xa01500, 0xb00400, 0xb00ff9],
+        result
+            .get_results()
+            .iter()
+            .map(|x| x.va)
+            .collect::<Vec<u64>>()
+    );
+}
+
+#[test]
+fn search_self_overlapping_needle_parallel() {
+    use crate::search::bytes_search::{search_memory_generic, search_pattern_parallel_chunked};
+    use memchr::memmem;
+
+    let attr = PageAttributes {
+        accessed: false,
+        dirty: false,
+        writeable: false,
+        user: false,
+        pwt: false,
+        pcd: false,
+        pat: false,
+        global: false,
+        nx: true,
+    };
+    let ranges = vec![
+        X86PageRange::new(0x10000, 0x100, attr.clone(), vec![PhysRange::new(0, 0x100)]),
+        X86PageRange::new(
+            0x10100,
+            0x100,
+            attr.clone(),
+            vec![PhysRange::new(0x100, 0x100)],
+        ),
+    ];
+
+    // Runs of 'a' of every length up to 12, which also cross the range boundary
+    let mem: Vec<u8> = (0..0x200)
+        .map(|i| if i % 13 == 12 { b'b' } else { b'a' })
+        .collect();
+    let mut memory_view = MemoryViewFromArray::from(&mem);
+    for needle in ["aa", "aaa", "aaaaa"] {
+        let needle = needle.as_bytes();
+        for alignment in [None, Some(2)] {
+            for max_num_occurrences in [5, 1000] {
+                // The ranges are shorter than a chunk, so they are searched in a single pass.
+                let expected = search_memory_generic(
+                    needle,
+                    &ranges,
+                    &mut memory_view,
+                    alignment,
+                    max_num_occurrences,
+                );
+                for chunk_size in [5, 6, 7, 8, 0x10, 0x100] {
+                    let result = search_pattern_parallel_chunked(
+                        &memmem::Finder::new(needle),
+                        &ranges,
+                        &memory_view,
+                        alignment,
+                        max_num_occurrences,
+                        chunk_size,
+                    );
+                    assert_eq!(
+                        expected
+                            .get_results()
+                            .iter()
+                            .map(|x| (x.va, x.range_index))
+                            .collect::<Vec<_>>(),
+                        result
+                            .get_results()
+                            .iter()
+                            .map(|x| (x.va, x.range_index))
+                            .collect::<Vec<_>>()
+                    );
+                }
+            }
+        }
+    }
+
+    // "aa" in "aaaa" crossing the chunk boundary at 0x10003
+    let result = search_pattern_parallel_chunked(
+        &memmem::Finder::new(b"aa"),
+        &ranges,
+        &memory_view,
+        None,
+        3,
+        3,
+    );
+    assert_eq!(
+        vec![0x10000, 0x10002, 0x10004],
+        result
+            .get_results()
+            .iter()
+            .map(|x| x.va)
+            .collect::<Vec<u64>>()
+    );
+}
+
+#[test]
+fn search_parallel_stops_early() {
+    use crate::search::bytes_search::search_pattern_parallel_chunked;
+    use memchr::memmem;
+
+    let attr = PageAttributes {
+        accessed: false,
+        dirty: false,
+        writeable: false,
+        user: false,
+        pwt: false,
+        pcd: false,
+        pat: false,
+        global: false,
+        nx: true,
+    };
+    let mut mem = vec![0u8; 0x100000];
+    let ranges = vec![X86PageRange::new(
+        0xa00000,
+        0x100000,
+        attr.clone(),
+        vec![PhysRange::new(0, 0x100000)],
+    )];
+    let needle = "KeyWord".as_byte_slice();
+    mem[0x10..0x10 + needle.len()].copy_from_slice(needle);
+    mem[0x80000..0x80000 + needle.len()].copy_from_slice(needle);
+    let memory_view = CountingMemoryView::from(&mem);
+
+    let num_chunks = 0x100000 / 0x100;
+    let result = search_pattern_parallel_chunked(
+        &memmem::Finder::new(&needle),
+        &ranges,
+        &memory_view,
+        None,
+        1,
+        0x100,
+    );
+    assert_eq!(
+        vec![0xa00010],
+        result
+            .get_results()
+            .iter()
+            .map(|x| x.va)
+            .collect::<Vec<u64>>()
+    );
+    // The chunks after the first one are skipped once it has filled the result.
+    assert!(memory_view.get_num_reads() < num_chunks / 2);
+}
+
+#[test]
+fn search_walk_stops_early() {
+    use crate::pt::x86::{self, X86Flavour};
+    use crate::search::bytes_search::search_memory_walk;
+
+    let mut mem = vec![0u64; 512 * 5]; // 5 physical pages
+    mem[1..9].fill(0x1003); // PML4Es, each mapping the same frame
+    mem[512] = 0x2003; // PDPTE
+    mem[512 * 2] = 0x3003; // PDE
+    mem[512 * 3] = 0x4003; // PTE
+    let needle = "KeyWord".as_byte_slice();
+    let mut mem_as_u8 = mem.as_byte_slice().to_vec();
+    mem_as_u8[0x4010..0x4017].copy_from_slice(needle);
+    let mut full_memory_view = CountingMemoryView::from(&mem_as_u8);
+    let ranges =
+        x86::collect_pages(X86Flavour::X64, &mut full_memory_view, 0, true, true, false).unwrap();
+    assert_eq!(8, ranges.len());
+
+    let mut walk_memory_view = CountingMemoryView::from(&mem_as_u8);
+    let mut memory_view = MemoryViewFromArray::from(&mem_as_u8);
+    let walk = x86::walk(X86Flavour::X64, &mut walk_memory_view, 0, true, true, false).unwrap();
+    let (found_ranges, result) = search_memory_walk(needle, walk, &mut memory_view, None, 2);
+    assert_eq!(vec![ranges[0].clone(), ranges[1].clone()], found_ranges);
+    assert_eq!(
+        vec![(0x80_0000_0010, 0), (0x100_0000_0010, 1)],
+        result
+            .get_results()
+            .iter()
+            .map(|x| (x.va, x.range_index))
+            .collect::<Vec<_>>()
+    );
+    // The tables mapping the ranges after the last occurrence aren't read.
+    assert!(walk_memory_view.get_num_reads() < full_memory_view.get_num_reads());
+}
+
+#[test]
+fn search_for_bytes_across_physical_ranges() {
+    use crate::search::bytes_search::{search_memory_generic, search_pattern_parallel_chunked};
+    use memchr::memmem;
+
+    let attr = PageAttributes {
+        accessed: false,
+        dirty: false,
+        writeable: false,
+        user: false,
+        pwt: false,
+        pcd: false,
+        pat: false,
+        global: false,
+        nx: true,
+    };
+
+    let mut mem = vec![0u8; 0x10000];
+    let ranges = vec![X86PageRange::new(
+        0xa00000,
+        0x3000,
+        attr.clone(),
+        vec![
+            PhysRange::new(0x5000, 0x1000),
+            PhysRange::new(0x1000, 0x1000),
+            PhysRange::new(0x8000, 0x1000),
+        ],
+    )];
+
+    let needle = "KeyWord".as_byte_slice();
+    mem[0x5ffd..0x6000].copy_from_slice(&needle[..3]);
+    mem[0x1000..0x1004].copy_from_slice(&needle[3..]);
+    mem[0x1ffa..0x2000].copy_from_slice(&needle[..6]);
+    mem[0x8000..0x8001].copy_from_slice(&needle[6..]);
+    // Physically contiguous, but not virtually
+    mem[0x4ffd..0x5000].copy_from_slice(&needle[..3]);
+    mem[0x5000..0x5004].copy_from_slice(&needle[3..]);
+    let mem_as_u8 = mem.as_byte_slice();
+    let mut memory_view = MemoryViewFromArray::from(&mem_as_u8);
+
+    let result = search_memory_generic(&needle, &ranges, &mut memory_view, None, 100);
+    assert_eq!(
+        vec![0xa00ffd, 0xa01ffa],
+        result
+            .get_results()
+            .iter()
+            .map(|x| x.va)
+            .collect::<Vec<u64>>()
+    );
+    for chunk_size in [0x3, 0x800, 0x1000, 0x10000] {
+        let result = search_pattern_parallel_chunked(
+            &memmem::Finder::new(&needle),
+            &ranges,
+            &memory_view,
+            None,
+            100,
+            chunk_size,
+        );
+        assert_eq!(
+            vec![0xa00ffd, 0xa01ffa],
+            result
+                .get_results()
+                .iter()
+                .map(|x| x.va)
+                .collect::<Vec<u64>>()
+        );
+    }
+}
+
+#[test]
+fn search_for_bytes_in_repeated_frame() {
+    use crate::pt::x86::{self, X86Flavour};
+    use crate::search::bytes_search::{search_memory_generic, search_pattern_parallel_chunked};
+    use memchr::memmem;
+
+    let mut mem = vec![0u64; 512 * 16]; // 16 physical pages
+    mem[0] = 0x1003; // PML4E
+    mem[512 * 1] = 0x2003; // PDPTE
+    mem[512 * 2] = 0x3003; // PDE
+    mem[512 * 3] = 0x5003; // PTE
+    mem[512 * 3 + 1] = 0x5003; // The same frame again
+    mem[512 * 3 + 2] = 0x9003;
+    let needle = "KeyWord".as_byte_slice();
+    let mut mem_as_u8 = mem.as_byte_slice().to_vec();
+    mem_as_u8[0x5010..0x5017].copy_from_slice(&needle);
+    mem_as_u8[0x9ff9..0xa000].copy_from_slice(&needle);
+    let mut memory_view = MemoryViewFromArray::from(&mem_as_u8);
+    let ranges =
+        x86::collect_pages(X86Flavour::X64, &mut memory_view, 0, true, true, false).unwrap();
+    assert_eq!(1, ranges.len());
+
+    let expected = vec![0x10, 0x1010, 0x2ff9];
+    let result = search_memory_generic(&needle, &ranges, &mut memory_view, None, 100);
+    assert_eq!(
+        expected,
+        result
+            .get_results()
+            .iter()
+            .map(|x| x.va)
+            .collect::<Vec<u64>>()
+    );
+    for chunk_size in [0x800, 0x1000, 0x10000] {
+        let result = search_pattern_parallel_chunked(
+            &memmem::Finder::new(&needle),
+            &ranges,
+            &memory_view,
+            None,
+            100,
+            chunk_size,
+        );
+        assert_eq!(
+            expected,
+            result
+                .get_results()
+                .iter()
+                .map(|x| x.va)
+                .collect::<Vec<u64>>()
+        );
+    }
+}
+
+#[test]
+fn search_for_byte_pattern() {
+    use crate::search::bytes_search::{
+        search_memory_pattern, search_pattern_parallel_chunked, BytePattern,
+    };
+
+    let attr = PageAttributes {
+        accessed: false,
+        dirty: false,
+        writeable: false,
+        user: false,
+        pwt: false,
+        pcd: false,
+        pat: false,
+        global: false,
+        nx: true,
+    };
+
+    assert!(BytePattern::parse("").is_err());
+    assert!(BytePattern::parse("48 8").is_err());
+    assert!(BytePattern::parse("48 8g").is_err());
+    assert!(BytePattern::new(&[0x48], &[0xff, 0xff]).is_err());
+    let pattern = BytePattern::parse("48 8b ?? ?? 00 00 e8").unwrap();
+    assert_eq!(7, pattern.len());
+    assert!(pattern.is_match(&[0x48, 0x8b, 0x12, 0x34, 0x00, 0x00, 0xe8]));
+    assert!(!pattern.is_match(&[0x48, 0x8b, 0x12, 0x34, 0x00, 0x01, 0xe8]));
+    let nibble_pattern = BytePattern::parse("4? ?b").unwrap();
+    assert!(nibble_pattern.is_match(&[0x41, 0xcb]));
+    assert!(!nibble_pattern.is_match(&[0x51, 0xcb]));
+
+    let mut mem = vec![0u8; 0x10000];
+    let ranges = vec![X86PageRange::new(
+        0xa00000,
+        0x2000,
+        attr.clone(),
+        vec![
+            PhysRange::new(0x5000, 0x1000),
+            PhysRange::new(0x1000, 0x1000),
+        ],
+    )];
+    mem[0x5100..0x5107].copy_from_slice(&[0x48, 0x8b, 0x05, 0x10, 0x00, 0x00, 0xe8]);
+    mem[0x5200..0x5207].copy_from_slice(&[0x48, 0x8b, 0x0d, 0x20, 0x00, 0x00, 0xe8]);
+    mem[0x5300..0x5307].copy_from_slice(&[0x48, 0x8b, 0x0d, 0x20, 0x00, 0x01, 0xe8]);
+    // Crosses the physical discontinuity
+    mem[0x5ffc..0x6000].copy_from_slice(&[0x48, 0x8b, 0xff, 0xff]);
+    mem[0x1000..0x1003].copy_from_slice(&[0x00, 0x00, 0xe8]);
+    let mem_as_u8 = mem.as_byte_slice();
+    let mut memory_view = MemoryViewFromArray::from(&mem_as_u8);
+
+    let result = search_memory_pattern(&pattern, &ranges, &mut memory_view, None, 100);
+    assert_eq!(
+        vec![0xa00100, 0xa00200, 0xa00ffc],
+        result
+            .get_results()
+            .iter()
+            .map(|x| x.va)
+            .collect::<Vec<u64>>()
+    );
+    for chunk_size in [0x5, 0x100, 0x10000] {
+        let result =
+            search_pattern_parallel_chunked(&pattern, &ranges, &memory_view, None, 100, chunk_size);
+        assert_eq!(
+            vec![0xa00100, 0xa00200, 0xa00ffc],
+            result
+                .get_results()
+                .iter()
+                .map(|x| x.va)
+                .collect::<Vec<u64>>()
+        );
+    }
+
+    // Without any fully masked byte every offset is checked.
+    let wildcard_pattern = BytePattern::parse("4? ?? ?d").unwrap();
+    let result = search_memory_pattern(&wildcard_pattern, &ranges, &mut memory_view, None, 100);
+    assert_eq!(
+        vec![0xa00200, 0xa00300],
+        result
+            .get_results()
+            .iter()
+            .map(|x| x.va)
+            .collect::<Vec<u64>>()
+    );
+
+    // Overlapping matches are reported.
+    let zeros_pattern = BytePattern::parse("00 00").unwrap();
+    let result = search_memory_pattern(&zeros_pattern, &ranges, &mut memory_view, None, 3);
+    assert_eq!(
+        vec![0xa00000, 0xa00001, 0xa00002],
+        result
+            .get_results()
+            .iter()
+            .map(|x| x.va)
+            .collect::<Vec<u64>>()
+    );
+}
+
+#[test]
+fn search_for_regex() {
+    use crate::search::bytes_search::{
+        search_memory_pattern, search_pattern_parallel_chunked, RegexPattern,
+    };
+
+    let attr = PageAttributes {
+        accessed: false,
+        dirty: false,
+        writeable: false,
+        user: false,
+        pwt: false,
+        pcd: false,
+        pat: false,
+        global: false,
+        nx: true,
+    };
+
+    assert!(RegexPattern::new("(", 0x100).is_err());
+    assert!(RegexPattern::new("a", 0).is_err());
+    assert!(RegexPattern::new("a", usize::MAX).is_err());
+
+    let mut mem = vec![0u8; 0x10000];
+    let ranges = vec![X86PageRange::new(
+        0xa00000,
+        0x2000,
+        attr.clone(),
+        vec![
+            PhysRange::new(0x5000, 0x1000),
+            PhysRange::new(0x1000, 0x1000),
+        ],
+    )];
+    let url = b"https://example.com/index.html";
+    mem[0x5100..0x5100 + url.len()].copy_from_slice(url);
+    // Crosses the physical discontinuity
+    mem[0x5ff0..0x6000].copy_from_slice(&url[..0x10]);
+    mem[0x1000..0x1000 + url.len() - 0x10].copy_from_slice(&url[0x10..]);
+    mem[0x1800..0x1808].copy_from_slice(b"http://a");
+    let mem_as_u8 = mem.as_byte_slice();
+    let mut memory_view = MemoryViewFromArray::from(&mem_as_u8);
+
+    let pattern = RegexPattern::new(r"https?://[a-z./]+", 0x100).unwrap();
+    let expected = vec![
+        (0xa00100, url.len() as u64),
+        (0xa00ff0, url.len() as u64),
+        (0xa01800, 8),
+    ];
+    let result = search_memory_pattern(&pattern, &ranges, &mut memory_view, None, 100);
+    assert_eq!(
+        expected,
+        result
+            .get_results()
+            .iter()
+            .map(|x| (x.va, x.length))
+            .collect::<Vec<_>>()
+    );
+    for chunk_size in [0x10, 0x800, 0x10000] {
+        let result =
+            search_pattern_parallel_chunked(&pattern, &ranges, &memory_view, None, 100, chunk_size);
+        assert_eq!(
+            expected,
+            result
+                .get_results()
+                .iter()
+                .map(|x| (x.va, x.length))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    // An unlimited number of occurrences doesn't overflow when the overlap is added.
+    let result = search_memory_pattern(&pattern, &ranges, &mut memory_view, None, usize::MAX);
+    assert_eq!(expected.len(), result.get_results().len());
+    let result =
+        search_pattern_parallel_chunked(&pattern, &ranges, &memory_view, None, usize::MAX, 0x800);
+    assert_eq!(expected.len(), result.get_results().len());
+
+    // Matches which continue in the next chunk aren't reported again by the next chunk.
+    let pattern = RegexPattern::new(r"[a-z]+", 0x100).unwrap();
+    let result = search_pattern_parallel_chunked(&pattern, &ranges, &memory_view, None, 2, 0x4);
+    assert_eq!(
+        vec![(0xa00100, 5), (0xa00108, 7)],
+        result
+            .get_results()
+            .iter()
+            .map(|x| (x.va, x.length))
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn search_for_multiple_needles() {
+    use crate::search::bytes_search::{
+        search_memory_multi, search_pattern_parallel_chunked, MultiNeedlePattern,
+    };
+
+    let attr = PageAttributes {
+        accessed: false,
+        dirty: false,
+        writeable: false,
+        user: false,
+        pwt: false,
+        pcd: false,
+        pat: false,
+        global: false,
+        nx: true,
+    };
+
+    assert!(MultiNeedlePattern::new::<&[u8]>(&[]).is_err());
+    assert!(MultiNeedlePattern::new(&["a", ""]).is_err());
+
+    let mut mem = vec![0u8; 0x10000];
+    let ranges = vec![
+        X86PageRange::new(
+            0xa00000,
+            0x2000,
+            attr.clone(),
+            vec![
+                PhysRange::new(0x5000, 0x1000),
+                PhysRange::new(0x1000, 0x1000),
+            ],
+        ),
+        X86PageRange::new(
+            0xb00000,
+            0x1000,
+            attr.clone(),
+            vec![PhysRange::new(0x9000, 0x1000)],
+        ),
+    ];
+    mem[0x5100..0x5107].copy_from_slice(b"Canary1");
+    // Crosses the physical discontinuity
+    mem[0x5ffe..0x6000].copy_from_slice(b"Se");
+    mem[0x1000..0x1004].copy_from_slice(b"cret");
+    mem[0x9200..0x9207].copy_from_slice(b"Canary2");
+    mem[0x9400..0x9407].copy_from_slice(b"Canary1");
+    let mem_as_u8 = mem.as_byte_slice();
+    let mut memory_view = MemoryViewFromArray::from(&mem_as_u8);
+
+    let needles = ["Canary1", "Secret", "Canary"];
+    let expected = vec![
+        (0xa00100, 0, 0, 7),
+        (0xa00100, 0, 2, 6),
+        (0xa00ffe, 0, 1, 6),
+        (0xb00200, 1, 2, 6),
+        (0xb00400, 1, 0, 7),
+        (0xb00400, 1, 2, 6),
+    ];
+    let result = search_memory_multi(&needles, &ranges, &mut memory_view, None, 100).unwrap();
+    assert_eq!(
+        expected,
+        result
+            .get_results()
+            .iter()
+            .map(|x| (x.va, x.range_index, x.needle_index, x.length))
+            .collect::<Vec<_>>()
+    );
+    let pattern = MultiNeedlePattern::new(&needles).unwrap();
+    for chunk_size in [0x3, 0x100, 0x10000] {
+        let result =
+            search_pattern_parallel_chunked(&pattern, &ranges, &memory_view, None, 100, chunk_size);
+        assert_eq!(
+            expected,
+            result
+                .get_results()
+                .iter()
+                .map(|x| (x.va, x.range_index, x.needle_index, x.length))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    let result = search_memory_multi(&needles, &ranges, &mut memory_view, None, 4).unwrap();
+    assert_eq!(4, result.get_results().len());
+}
+
+#[test]
+fn search_for_pointers() {
+    use crate::search::bytes_search::{
+        search_memory_pattern, search_pattern_parallel_chunked, PointerPattern,
+    };
+
+    let attr = PageAttributes {
+        accessed: false,
+        dirty: false,
+        writeable: false,
+        user: false,
+        pwt: false,
+        pcd: false,
+        pat: false,
+        global: false,
+        nx: true,
+    };
+
+    assert!(PointerPattern::new(2, 0, 0x1000).is_err());
+    assert!(PointerPattern::new(8, 0x1000, 0x1000).is_err());
+
+    let mut mem = vec![0u8; 0x10000];
+    let ranges = vec![
+        X86PageRange::new(
+            0xffff800000000000,
+            0x2000,
+            attr.clone(),
+            vec![
+                PhysRange::new(0x5000, 0x1000),
+                PhysRange::new(0x1000, 0x1000),
+            ],
+        ),
+        X86PageRange::new(
+            0xffff800000004000,
+            0x1000,
+            attr.clone(),
+            vec![PhysRange::new(0x9000, 0x1000)],
+        ),
+    ];
+    let mut write_u64 = |off: usize, value: u64| {
+        mem[off..off + 8].copy_from_slice(&value.to_le_bytes());
+    };
+    write_u64(0x5100, 0xffff800000000010);
+    write_u64(0x5203, 0xffff800000004020); // Unaligned
+    write_u64(0x9010, 0xffff800000004ff8);
+    write_u64(0x9020, 0xffff800000005000); // Out of the interval
+
+    // Crosses the physical discontinuity, not mapped
+    let crossing_value = 0xffff800000003000u64.to_le_bytes();
+    mem[0x5ffc..0x6000].copy_from_slice(&crossing_value[..4]);
+    mem[0x1000..0x1004].copy_from_slice(&crossing_value[4..]);
+    let mem_as_u8 = mem.as_byte_slice();
+    let mut memory_view = MemoryViewFromArray::from(&mem_as_u8);
+
+    let pattern = PointerPattern::new(8, 0xffff800000000000, 0xffff800000005000).unwrap();
+    let get_vas = |result: &crate::search::bytes_search::SearchResult| {
+        result
+            .get_results()
+            .iter()
+            .map(|x| x.va)
+            .collect::<Vec<u64>>()
+    };
+    let result = search_memory_pattern(&pattern, &ranges, &mut memory_view, Some(8), 100);
+    assert_eq!(
+        vec![0xffff800000000100, 0xffff800000004010],
+        get_vas(&result)
+    );
+    let result = search_memory_pattern(&pattern, &ranges, &mut memory_view, None, 100);
+    let expected = vec![
+        0xffff800000000100,
+        0xffff800000000203,
+        0xffff800000000ffc,
+        0xffff800000004010,
+    ];
+    assert_eq!(expected, get_vas(&result));
+    for chunk_size in [0x5, 0x800, 0x10000] {
+        let result =
+            search_pattern_parallel_chunked(&pattern, &ranges, &memory_view, None, 100, chunk_size);
+        assert_eq!(expected, get_vas(&result));
+    }
+
+    let pattern = pattern.restrict_to_mapped(&ranges);
+    assert!(pattern.is_match(0xffff800000001fff));
+    assert!(!pattern.is_match(0xffff800000002000));
+    let result = search_memory_pattern(&pattern, &ranges, &mut memory_view, None, 100);
+    assert_eq!(
+        vec![0xffff800000000100, 0xffff800000000203, 0xffff800000004010],
+        get_vas(&result)
+    );
+
+    // 4-byte pointers
+    let pattern = PointerPattern::new(4, 0x4000, 0x5000).unwrap();
+    let result = search_memory_pattern(&pattern, &ranges, &mut memory_view, Some(4), 100);
+    assert_eq!(vec![0xffff800000004010], get_vas(&result));
+}
+
+#[test]
+fn reverse_mapping_of_physical_interval() {
+    use crate::search::reverse_mapping::{find_virtual_addresses, ReverseMapping};
+
+    let attr = PageAttributes {
+        accessed: false,
+        dirty: false,
+        writeable: false,
+        user: false,
+        pwt: false,
+        pcd: false,
+        pat: false,
+        global: false,
+        nx: true,
+    };
+    let ranges = vec![
+        X86PageRange::new(
+            0x10000,
+            0x3000,
+            attr.clone(),
+            vec![
+                PhysRange::new(0x5000, 0x1000),
+                PhysRange::new(0x1000, 0x2000),
+            ],
+        ),
+        X86PageRange::new(
+            0xffff800000000000,
+            0x4000,
+            attr.clone(),
+            vec![PhysRange::new(0x0, 0x4000)],
+        ),
+    ];
+
+    assert_eq!(
+        vec![
+            ReverseMapping {
+                va: 0x11800,
+                pa: 0x1800,
+                extent: 0x800,
+                range_index: 0
+            },
+            ReverseMapping {
+                va: 0xffff800000001800,
+                pa: 0x1800,
+                extent: 0x800,
+                range_index: 1
+            },
+        ],
+        find_virtual_addresses(&ranges, 0x1800, 0x800)
+    );
+    assert_eq!(
+        vec![ReverseMapping {
+            va: 0x10fff,
+            pa: 0x5fff,
+            extent: 0x1,
+            range_index: 0
+        },],
+        find_virtual_addresses(&ranges, 0x5fff, 0x1)
+    );
+    assert_eq!(
+        Vec::<ReverseMapping>::new(),
+        find_virtual_addresses(&ranges, 0x4000, 0x1000)
+    );
+    assert_eq!(
+        Vec::<ReverseMapping>::new(),
+        find_virtual_addresses(&ranges, 0x5000, 0)
+    );
+    assert_eq!(
+        Vec::<ReverseMapping>::new(),
+        find_virtual_addresses(&ranges, u64::MAX, 0x1000)
+    );
+}
+
+#[test]
+fn reverse_mapping_of_repeated_frame() {
+    use crate::pt::x86::{self, X86Flavour};
+    use crate::search::reverse_mapping::{find_virtual_addresses, ReverseMapping};
+
+    let mut mem = vec![0u64; 512 * 16]; // 16 physical pages
+    mem[0] = 0x1003; // PML4E
+    mem[512 * 1] = 0x2003; // PDPTE
+    mem[512 * 2] = 0x3003; // PDE
+    mem[512 * 3] = 0x5003; // PTE
+    mem[512 * 3 + 1] = 0x5003; // The same frame again
+    mem[512 * 3 + 2] = 0x9003;
+    let mem_as_u8 = mem.as_byte_slice();
+    let mut memory_view = MemoryViewFromArray::from(&mem_as_u8);
+    let ranges =
+        x86::collect_pages(X86Flavour::X64, &mut memory_view, 0, true, true, false).unwrap();
+    assert_eq!(1, ranges.len());
+    // The repeated frame is kept, so that the physical ranges describe the range in order
+    assert_eq!(
+        &vec![
+            PhysRange::new(0x5000, 0x1000),
+            PhysRange::new(0x5000, 0x1000),
+            PhysRange::new(0x9000, 0x1000)
+        ],
+        ranges[0].get_phys_ranges()
+    );
+
+    assert_eq!(
+        vec![ReverseMapping {
+            va: 0x2000,
+            pa: 0x9000,
+            extent: 0x1000,
+            range_index: 0
+        }],
+        find_virtual_addresses(&ranges, 0x9000, 0x1000)
+    );
+    assert_eq!(
+        vec![0x0, 0x1000],
+        find_virtual_addresses(&ranges, 0x5000, 0x1000)
+            .iter()
+            .map(|x| x.va)
+            .collect::<Vec<u64>>()
+    );
+}
+
+#[test]
+fn scan_for_x86_64_roots() {
+    use crate::search::root_scan::{scan_for_roots, validate_root, RootScanFlavour};
+
+    let mut mem = vec![0u64; 512 * 16]; // 16 physical pages
+
+    // Two processes sharing the kernel half
+    mem[512 * 1 + 1] = 0x4027; // PML4E, accessed
+    mem[512 * 1 + 256] = 0x3063; // PML4E, accessed and dirty
+    mem[512 * 2 + 1] = 0x5007;
+    mem[512 * 2 + 256] = 0x3003;
+    mem[512 * 3] = 0x83; // 1 GiB page
+    mem[512 * 4] = 0x6007; // PDPTE
+    mem[512 * 6] = 0x7007; // PDE
+    mem[512 * 7] = 0x100000007; // PTE outside of physical memory
+    mem[512 * 7 + 1] = 0x100001007;
+    // Data pages
+    mem[512 * 8] = 0x200000001;
+    mem[512 * 10] = 1;
+    mem[512 * 10 + 1] = 3;
+    // Self-referencing
+    mem[512 * 9 + 10] = 0x9003;
+    let mem_as_u8 = mem.as_byte_slice();
+    let mut memory_view = MemoryViewFromArray::from(&mem_as_u8);
+    let phys_ranges = vec![PhysRange::new(0, 0x10000)];
+
+    let candidates = scan_for_roots(&mut memory_view, &phys_ranges, RootScanFlavour::X86_64);
+    assert_eq!(
+        vec![
+            (0x9000, 65, 0),
+            (0x1000, 10, 1),
+            (0x2000, 10, 1),
+            (0x4000, 1, 0),
+            (0x6000, 1, 0)
+        ],
+        candidates
+            .iter()
+            .map(|x| (x.pa, x.score, x.shared_kernel_half))
+            .collect::<Vec<_>>()
+    );
+    assert!(candidates[0].self_reference);
+    assert_eq!(1, candidates[1].user_entries);
+    assert_eq!(1, candidates[1].kernel_entries);
+
+    let validation = validate_root(
+        &mut memory_view,
+        &phys_ranges,
+        RootScanFlavour::X86_64,
+        0x1000,
+        16,
+    )
+    .unwrap();
+    assert_eq!(5, validation.tables_visited);
+    assert_eq!(3, validation.leaf_entries);
+    assert!(validation.is_plausible());
+    assert!(!validation.truncated);
+
+    let validation = validate_root(
+        &mut memory_view,
+        &phys_ranges,
+        RootScanFlavour::X86_64,
+        0x1000,
+        2,
+    )
+    .unwrap();
+    assert!(validation.truncated);
+
+    // The PT is interpreted as a PDPT, pointing to tables outside of physical memory.
+    let validation = validate_root(
+        &mut memory_view,
+        &phys_ranges,
+        RootScanFlavour::X86_64,
+        0x6000,
+        16,
+    )
+    .unwrap();
+    assert_eq!(2, validation.invalid_entries);
+    assert!(!validation.is_plausible());
+
+    assert!(validate_root(
+        &mut memory_view,
+        &phys_ranges,
+        RootScanFlavour::X86_64,
+        0x100000,
+        16
+    )
+    .is_err());
+}
+
+#[test]
+fn scan_for_aarch64_roots() {
+    use crate::search::root_scan::{scan_for_roots, validate_root, RootScanFlavour};
+
+    let mut mem = vec![0u64; 512 * 8]; // 8 physical pages
+    mem[512 * 1] = 0x2003; // L0 table descriptor
+    mem[512 * 2] = 0x40000701; // L1 block
+    mem[512 * 2 + 1] = 0x3003;
+    mem[512 * 3] = 0x4003; // L2 table descriptor
+    mem[512 * 4] = 0x40000703; // L3 page
+    mem[512 * 4 + 1] = 0x40001701; // Reserved at L3
+    mem[512 * 5] = 0x40000701; // An L0 block is reserved
+    let mem_as_u8 = mem.as_byte_slice();
+    let mut memory_view = MemoryViewFromArray::from(&mem_as_u8);
+    let phys_ranges = vec![PhysRange::new(0, 0x8000)];
+
+    let candidates = scan_for_roots(&mut memory_view, &phys_ranges, RootScanFlavour::Aarch64);
+    assert_eq!(
+        vec![0x1000, 0x3000],
+        candidates.iter().map(|x| x.pa).collect::<Vec<_>>()
+    );
+    // The entries aren't split into halves on AArch64.
+    assert_eq!(
+        (1, 0, 0),
+        (
+            candidates[0].present_entries,
+            candidates[0].user_entries,
+            candidates[0].kernel_entries
+        )
+    );
+
+    let validation = validate_root(
+        &mut memory_view,
+        &phys_ranges,
+        RootScanFlavour::Aarch64,
+        0x1000,
+        16,
+    )
+    .unwrap();
+    assert_eq!(4, validation.tables_visited);
+    assert_eq!(2, validation.leaf_entries);
+    assert_eq!(1, validation.invalid_entries);
+}
+
+#[test]
+fn group_roots_into_address_spaces() {
+    use crate::search::address_spaces::{group_into_address_spaces, UserSpaceSummary};
+    use crate::search::root_scan::{scan_for_roots, RootScanFlavour};
+
+    let mut mem = vec![0u64; 512 * 16]; // 16 physical pages
+
+    // Two processes sharing the kernel half
+    mem[512 * 1 + 1] = 0x4027; // PML4E, accessed
+    mem[512 * 1 + 256] = 0x3063; // PML4E, accessed and dirty
+    mem[512 * 2 + 1] = 0x5007;
+    mem[512 * 2 + 256] = 0x3003;
+    mem[512 * 3] = 0x83; // 1 GiB kernel page
+    mem[512 * 4] = 0x6007; // PDPTE
+    mem[512 * 6] = 0x7007; // PDE
+    mem[512 * 7] = 0x100000007; // PTE
+    mem[512 * 7 + 1] = 0x8000000100001005; // Read-only, NX
+    mem[512 * 7 + 3] = 0x100003007;
+    // Another kernel
+    mem[512 * 8 + 511] = 0x3003;
+    let mem_as_u8 = mem.as_byte_slice();
+    let mut memory_view = MemoryViewFromArray::from(&mem_as_u8);
+    let phys_ranges = vec![PhysRange::new(0, 0x10000)];
+
+    let candidates = scan_for_roots(&mut memory_view, &phys_ranges, RootScanFlavour::X86_64);
+    let kernels = group_into_address_spaces(&mut memory_view, &candidates);
+    assert_eq!(2, kernels.len());
+    assert_eq!(0x3063, kernels[0].kernel_entries[0]);
+    assert_eq!(
+        vec![0x1000, 0x2000],
+        kernels[0]
+            .processes
+            .iter()
+            .map(|x| x.root_pa)
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(
+        UserSpaceSummary {
+            ranges: 3,
+            mapped_bytes: 0x3000,
+            writeable_bytes: 0x2000,
+            executable_bytes: 0x2000,
+            lowest_va: Some(0x8000000000),
+            highest_va: Some(0x8000003fff),
+        },
+        kernels[0].processes[0].user_space
+    );
+    assert_eq!(
+        UserSpaceSummary::default(),
+        kernels[0].processes[1].user_space
+    );
+    assert_eq!(
+        vec![0x8000],
+        kernels[1]
+            .processes
+            .iter()
+            .map(|x| x.root_pa)
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn find_kaslr_for_linux_x86_64() {
+    use crate::search::kaslr::{find_kaslr_linux_x86_64, KaslrInfo};
+
+    let create_range = |va: u64, pa: u64, user: bool, nx: bool| {
+        let attr = PageAttributes {
+            accessed: false,
+            dirty: false,
+            writeable: false,
+            user: user,
+            pwt: false,
+            pcd: false,
+            pat: false,
+            global: false,
+            nx: nx,
+        };
+        X86PageRange::new(va, 0x200000, attr, vec![PhysRange::new(pa, 0x200000)])
+    };
+    let ranges = vec![
+        create_range(0x400000, 0x200000, true, false),
+        create_range(0xffff888000000000, 0, false, true),
+        create_range(0xffffffff81000000, 0x200000, false, false),
+    ];
+
+    let mut mem = vec![0u8; 0x400000];
+    mem[0] = 0x48;
+    mem[0x200000] = 0x48;
+    let mut memory_view = MemoryViewFromArray::from(&mem);
+    assert_eq!(
+        KaslrInfo {
+            image_virt: Some(0xffffffff81000000),
+            image_phys: Some(0x200000),
+            physmap_virt: Some(0xffff888000000000),
+        },
+        find_kaslr_linux_x86_64(&ranges, &mut memory_view)
+    );
+}
+
+#[test]
+fn find_kaslr_for_linux_aarch64() {
+    use crate::pt::arm::{ArmPageAttributes, ArmPageRange};
+    use crate::search::kaslr::{find_kaslr_linux_aarch64, KaslrInfo};
+
+    let create_range =
+        |va: u64, pa: u64, extent: u64, permission_bits: u8, pxn: bool| ArmPageRange {
+            va: va,
+            extent: extent,
+            phys_ranges: vec![PhysRange::new(pa, extent)],
+            attr: ArmPageAttributes {
+                permission_bits: permission_bits,
+                pxn: pxn,
+                xn: pxn,
+                ..Default::default()
+            },
+        };
+    let ranges = vec![
+        // User mapping of the image
+        create_range(0x400000, 0x200000, 0x200000, 0b01, false),
+        // Linear map of all memory, starting at physical address 0x100000
+        create_range(0xffff000000000000, 0x100000, 0x300000, 0b00, true),
+        // Kernel text, with a header-less page first
+        create_range(0xffff800008000000, 0x1ff000, 0x201000, 0b10, false),
+    ];
+
+    let mut mem = vec![0u8; 0x400000];
+    mem[0x200038..0x20003c].copy_from_slice(b"ARM\x64");
+    let mut memory_view = MemoryViewFromArray::from(&mem);
+    assert_eq!(
+        KaslrInfo {
+            image_virt: Some(0xffff800008001000),
+            image_phys: Some(0x200000),
+            physmap_virt: Some(0xfffefffffff00000),
+        },
+        find_kaslr_linux_aarch64(&ranges, &mut memory_view)
+    );
+
+    mem[0x200038] = 0;
+    let mut memory_view = MemoryViewFromArray::from(&mem);
+    assert_eq!(
+        KaslrInfo::default(),
+        find_kaslr_linux_aarch64(&ranges, &mut memory_view)
+    );
+}
+
+#[test]
+fn find_kaslr_for_linux_riscv64() {
+    use crate::pt::riscv::{RiscvPageAttributes, RiscvPageRange};
+    use crate::search::kaslr::{find_kaslr_linux_riscv64, KaslrInfo};
+
+    let create_range = |va: u64, pa: u64, extent: u64, user: bool, executable: bool| {
+        let attr = RiscvPageAttributes {
+            readable: true,
+            writeable: !executable,
+            executable: executable,
+            user: user,
+            global: !user,
+            accessed: true,
+            dirty: true,
+        };
+        RiscvPageRange::new(va, extent, attr, vec![PhysRange::new(pa, extent)])
+    };
+    let ranges = vec![
+        create_range(0x400000, 0x200000, 0x200000, true, true),
+        // Linear mapping at PAGE_OFFSET
+        create_range(0xff60000000000000, 0, 0x400000, false, false),
+        create_range(0xffffffff80000000, 0x200000, 0x200000, false, true),
+    ];
+
+    let mut mem = vec![0u8; 0x400000];
+    mem[0x200038..0x20003c].copy_from_slice(b"RSC\x05");
+    let mut memory_view = MemoryViewFromArray::from(&mem);
+    assert_eq!(
+        KaslrInfo {
+            image_virt: Some(0xffffffff80000000),
+            image_phys: Some(0x200000),
+            physmap_virt: Some(0xff60000000000000),
+        },
+        find_kaslr_linux_riscv64(&ranges, &mut memory_view)
+    );
+}
+
+#[test]
+fn find_kaslr_for_linux_x86_32() {
+    use crate::search::kaslr::{find_kaslr_linux_x86_32, KaslrInfo};
+
+    let create_range = |va: u64, pa: u64, extent: u64, nx: bool| {
+        let attr = PageAttributes {
+            accessed: false,
+            dirty: false,
+            writeable: nx,
+            user: false,
+            pwt: false,
+            pcd: false,
+            pat: false,
+            global: true,
+            nx: nx,
+        };
+        X86PageRange::new(va, extent, attr, vec![PhysRange::new(pa, extent)])
+    };
+    // Without PAE nothing is NX and the data in the direct mapping is executable too.
+    let create_ranges = |pae: bool| {
+        vec![
+            // BIOS area
+            create_range(0xc0000000, 0, 0x100000, false),
+            create_range(0xc0100000, 0x100000, 0x300000, pae),
+            // Kernel text at a randomized physical address
+            create_range(0xc0400000, 0x400000, 0x200000, false),
+        ]
+    };
+
+    let mut mem = vec![0u8; 0x600000];
+    mem[0..2].copy_from_slice(&[0x8b, 0x0d]);
+    mem[0x400000..0x400002].copy_from_slice(&[0x8b, 0x0d]);
+    for pae in [true, false] {
+        let mut memory_view = MemoryViewFromArray::from(&mem);
+        assert_eq!(
+            KaslrInfo {
+                image_virt: Some(0xc0400000),
+                image_phys: Some(0x400000),
+                physmap_virt: Some(0xc0000000),
+            },
+            find_kaslr_linux_x86_32(&create_ranges(pae), &mut memory_view)
+        );
+    }
+
+    mem[0x400000] = 0;
+    let mut memory_view = MemoryViewFromArray::from(&mem);
+    assert_eq!(
+        KaslrInfo::default(),
+        find_kaslr_linux_x86_32(&create_ranges(true), &mut memory_view)
+    );
+}
@@ -1,13 +1,26 @@
+use pt_dump_lib::diff::page_range_diff;
 use pt_dump_lib::filter::page_range_filter;
 use pt_dump_lib::filter::page_range_filter::PageRangeFilterX86;
-use pt_dump_lib::print::printer::{Aarch64Writer, Printer, X86Writer};
+use pt_dump_lib::memory::memory::MemoryView;
+use pt_dump_lib::memory::memory_virtual::VirtualMemoryView;
+use pt_dump_lib::print::printer::{
+    Aarch64Writer, DiffWriter, EptWriter, Printer, RiscvWriter, X86Writer,
+};
 use pt_dump_lib::pt::arm;
 use pt_dump_lib::pt::arm::ArmPageRange;
+use pt_dump_lib::pt::ept;
+use pt_dump_lib::pt::ept::EptPageRange;
 use pt_dump_lib::pt::page_range::*;
+use pt_dump_lib::pt::riscv;
+use pt_dump_lib::pt::riscv::RiscvPageRange;
 use pt_dump_lib::pt::x86::X86PageRange;
 use pt_dump_lib::pt::*;
+use pt_dump_lib::search::address_spaces;
 use pt_dump_lib::search::bytes_search::{self};
-use pyo3::exceptions::PyTypeError;
+use pt_dump_lib::search::kaslr;
+use pt_dump_lib::search::reverse_mapping;
+use pt_dump_lib::search::root_scan;
+use pyo3::exceptions::{PyTypeError, PyValueError};
 use pyo3::prelude::*;
 use pyo3::types::{PyBytes, PyList, PyTuple};
 use qemu_memory::QemuMemoryView;
@@ -21,6 +34,8 @@ struct PyFilterCommon {
     va_range: Option<(Option<u64>, Option<u64>)>,
     has_address: Option<u64>,
     only_superuser_accessible: Option<bool>,
+    global: Option<bool>,
+    device_memory: Option<bool>,
 }
 
 trait PageTable<PageType> {
@@ -78,6 +93,56 @@ impl PageTable<ArmPageRange> for PageTableAarch64 {
     }
 }
 
+#[pyclass]
+pub struct PageTableRiscv64 {
+    ranges: Vec<RiscvPageRange>,
+    memory_view: QemuMemoryView,
+}
+
+impl PageTableRiscv64 {
+    pub fn new(ranges: Vec<RiscvPageRange>, memory_view: QemuMemoryView) -> Self {
+        PageTableRiscv64 {
+            ranges: ranges,
+            memory_view: memory_view,
+        }
+    }
+}
+
+impl PageTable<RiscvPageRange> for PageTableRiscv64 {
+    fn get_ranges(&self) -> &Vec<RiscvPageRange> {
+        &self.ranges
+    }
+
+    fn get_memory_view(&mut self) -> &mut QemuMemoryView {
+        &mut self.memory_view
+    }
+}
+
+#[pyclass]
+pub struct PageTableEpt {
+    ranges: Vec<EptPageRange>,
+    memory_view: QemuMemoryView,
+}
+
+impl PageTableEpt {
+    pub fn new(ranges: Vec<EptPageRange>, memory_view: QemuMemoryView) -> Self {
+        PageTableEpt {
+            ranges: ranges,
+            memory_view: memory_view,
+        }
+    }
+}
+
+impl PageTable<EptPageRange> for PageTableEpt {
+    fn get_ranges(&self) -> &Vec<EptPageRange> {
+        &self.ranges
+    }
+
+    fn get_memory_view(&mut self) -> &mut QemuMemoryView {
+        &mut self.memory_view
+    }
+}
+
 #[pyfunction]
 fn get_page_table_as_string_x86(table: &PageTableX86) -> String {
     let mut writer = X86Writer::new();
@@ -94,6 +159,48 @@ fn get_page_table_as_string_aarch64(table: &PageTableAarch64) -> String {
     result.clone()
 }
 
+#[pyfunction]
+fn get_page_table_as_string_riscv64(table: &PageTableRiscv64) -> String {
+    let mut writer = RiscvWriter::new();
+    writer.write_ranges(&table.get_ranges());
+    let result = writer.get_result();
+    result.clone()
+}
+
+#[pyfunction]
+fn get_page_table_as_string_ept(table: &PageTableEpt) -> String {
+    let mut writer = EptWriter::new();
+    writer.write_ranges(&table.get_ranges());
+    let result = writer.get_result();
+    result.clone()
+}
+
+fn diff_page_tables_generic<
+    PageRangeType: GenericPageRange + GenericPagePermissions,
+    PageTableType: PageTable<PageRangeType>,
+>(
+    old_table: &PageTableType,
+    new_table: &PageTableType,
+) -> String {
+    let diffs = page_range_diff::diff_page_ranges(old_table.get_ranges(), new_table.get_ranges());
+    let mut writer = DiffWriter::new();
+    writer.write_diffs(&diffs, old_table.get_ranges(), new_table.get_ranges());
+    writer.get_result().clone()
+}
+
+#[pyfunction]
+fn diff_page_tables_x86(old_table: &PageTableX86, new_table: &PageTableX86) -> String {
+    diff_page_tables_generic(old_table, new_table)
+}
+
+#[pyfunction]
+fn diff_page_tables_aarch64(
+    old_table: &PageTableAarch64,
+    new_table: &PageTableAarch64,
+) -> String {
+    diff_page_tables_generic(old_table, new_table)
+}
+
 fn collect_ram_ranges(phys_ranges: &PyList) -> Result<Vec<qemu_memory::RamRange>, PyErr> {
     let mut phys_ranges_vec = vec![];
     for u in phys_ranges {
@@ -135,6 +242,20 @@ fn pages_to_ranges<PageType: GenericPage + Clone, RangeType: GenericPageRange +
     pages.iter().map(|x| RangeType::from(x.clone())).collect()
 }
 
+fn walk_page_table_x86<'a>(
+    memory_view: &'a mut QemuMemoryView,
+    flavour: x86::X86Flavour,
+    cr3: u64,
+    pse: bool,
+    pae: bool,
+    la57: bool,
+) -> PyResult<x86::X86PageWalker<'a>> {
+    match x86::walk(flavour, memory_view, cr3, pse, pae, la57) {
+        Ok(walker) => Ok(walker),
+        Err(_) => Err(PyTypeError::new_err("Failed to collect pages")),
+    }
+}
+
 #[pyfunction]
 fn parse_page_table_x86_32(
     fd: i32,
@@ -144,24 +265,131 @@ fn parse_page_table_x86_32(
     phys_ranges: &PyList,
 ) -> PyResult<PageTableX86> {
     let mut memory_view = create_memory_view(fd, &phys_ranges)?;
-    let pages = x86::collect_pages(x86::X86Flavour::X86, &mut memory_view, cr3, pse, pae);
+    // Tables below the root which can't be read are skipped.
+    let ranges = walk_page_table_x86(
+        &mut memory_view,
+        x86::X86Flavour::X86,
+        cr3,
+        pse,
+        pae,
+        false,
+    )?
+    .filter_map(Result::ok)
+    .collect();
+    Ok(PageTableX86::new(ranges, memory_view))
+}
+
+#[pyfunction(la57 = "false")]
+fn parse_page_table_x86_64(
+    fd: i32,
+    cr3: u64,
+    pae: bool,
+    pse: bool,
+    phys_ranges: &PyList,
+    la57: bool,
+) -> PyResult<PageTableX86> {
+    let mut memory_view = create_memory_view(fd, &phys_ranges)?;
+    // Tables below the root which can't be read are skipped.
+    let ranges = walk_page_table_x86(
+        &mut memory_view,
+        x86::X86Flavour::X64,
+        cr3,
+        pse,
+        pae,
+        la57,
+    )?
+    .filter_map(Result::ok)
+    .collect();
+    Ok(PageTableX86::new(ranges, memory_view))
+}
+
+// Walks the root table entries on multiple threads, each with its own clone of the memory view.
+#[pyfunction(la57 = "false")]
+fn parse_page_table_x86_64_parallel(
+    fd: i32,
+    cr3: u64,
+    pae: bool,
+    pse: bool,
+    phys_ranges: &PyList,
+    la57: bool,
+) -> PyResult<PageTableX86> {
+    let memory_view = create_memory_view(fd, &phys_ranges)?;
+    let pages =
+        x86::collect_pages_parallel(x86::X86Flavour::X64, &memory_view, cr3, pse, pae, la57);
     if let Ok(pages_ok) = pages {
-        Ok(PageTableX86::new(pages_to_ranges(&pages_ok), memory_view))
+        Ok(PageTableX86::new(pages_ok, memory_view))
     } else {
         return Err(PyTypeError::new_err("Failed to collect pages"));
     }
 }
 
-#[pyfunction]
-fn parse_page_table_x86_64(
+// Prints the page table while walking it, without collecting its ranges first.
+#[pyfunction(la57 = "false")]
+fn dump_page_table_x86_64(
+    fd: i32,
+    cr3: u64,
+    pae: bool,
+    pse: bool,
+    phys_ranges: &PyList,
+    la57: bool,
+) -> PyResult<String> {
+    let mut memory_view = create_memory_view(fd, &phys_ranges)?;
+    let walk = walk_page_table_x86(
+        &mut memory_view,
+        x86::X86Flavour::X64,
+        cr3,
+        pse,
+        pae,
+        la57,
+    )?;
+    let mut writer = X86Writer::new();
+    writer.write_walk(walk);
+    Ok(writer.get_result().clone())
+}
+
+// Searches the page table while walking it, the walk stops after max_found occurrences.
+#[pyfunction(la57 = "false")]
+fn search_memory_walk_x86_64(
     fd: i32,
     cr3: u64,
     pae: bool,
     pse: bool,
     phys_ranges: &PyList,
+    data_py: &PyBytes,
+    alignment: u64,
+    max_found: usize,
+    la57: bool,
+) -> PyResult<String> {
+    let mut memory_view = create_memory_view(fd, &phys_ranges)?;
+    let mut search_memory_view = memory_view.clone();
+    let walk = walk_page_table_x86(
+        &mut memory_view,
+        x86::X86Flavour::X64,
+        cr3,
+        pse,
+        pae,
+        la57,
+    )?;
+    let (ranges, search_result) = bytes_search::search_memory_walk(
+        data_py.as_bytes(),
+        walk,
+        &mut search_memory_view,
+        Some(alignment),
+        max_found,
+    );
+    Ok(search_result_to_string(&ranges, &search_result))
+}
+
+// The resulting "virtual" addresses are guest physical addresses.
+#[pyfunction(la57 = "false")]
+fn parse_page_table_npt(
+    fd: i32,
+    ncr3: u64,
+    phys_ranges: &PyList,
+    la57: bool,
 ) -> PyResult<PageTableX86> {
     let mut memory_view = create_memory_view(fd, &phys_ranges)?;
-    let pages = x86::collect_pages(x86::X86Flavour::X64, &mut memory_view, cr3, pse, pae);
+    let pages = x86::collect_npt_pages(&mut memory_view, ncr3, la57);
     if let Ok(pages_ok) = pages {
         Ok(PageTableX86::new(pages_to_ranges(&pages_ok), memory_view))
     } else {
@@ -169,6 +397,106 @@ fn parse_page_table_x86_64(
     }
 }
 
+#[pyfunction(mbec = "false")]
+fn parse_page_table_ept(
+    fd: i32,
+    eptp: u64,
+    phys_ranges: &PyList,
+    mbec: bool,
+) -> PyResult<PageTableEpt> {
+    let mut memory_view = create_memory_view(fd, &phys_ranges)?;
+    let pages = ept::collect_pages_from_eptp(&mut memory_view, eptp, mbec);
+    if let Ok(pages_ok) = pages {
+        Ok(PageTableEpt::new(pages_to_ranges(&pages_ok), memory_view))
+    } else {
+        return Err(PyTypeError::new_err("Failed to collect pages"));
+    }
+}
+
+// The resulting "virtual" addresses are intermediate physical addresses.
+#[pyfunction]
+fn parse_page_table_aarch64_stage2(
+    fd: i32,
+    vttbr: u64,
+    vtcr: u64,
+    phys_ranges: &PyList,
+) -> PyResult<PageTableAarch64> {
+    let mut memory_view = create_memory_view(fd, &phys_ranges)?;
+    let pages = arm::collect_stage2_pages_from_vttbr(&mut memory_view, vttbr, vtcr);
+    if let Ok(pages_ok) = pages {
+        Ok(PageTableAarch64::new(
+            pages_to_ranges(&pages_ok),
+            memory_view,
+        ))
+    } else {
+        return Err(PyTypeError::new_err("Failed to collect pages"));
+    }
+}
+
+#[pyfunction(la57 = "false")]
+fn translate_x86_64(
+    fd: i32,
+    cr3: u64,
+    va: u64,
+    phys_ranges: &PyList,
+    la57: bool,
+) -> PyResult<String> {
+    let mut memory_view = create_memory_view(fd, &phys_ranges)?;
+    let translation =
+        x86::translate(x86::X86Flavour::X64, &mut memory_view, cr3, true, true, la57, va);
+    if let Ok(translation_ok) = translation {
+        Ok(translation_ok.to_string())
+    } else {
+        return Err(PyTypeError::new_err("Failed to translate address"));
+    }
+}
+
+#[pyfunction(hierarchical_permissions = "true", mair = "None")]
+fn translate_aarch64(
+    fd: i32,
+    pt_pa: u64,
+    va: u64,
+    address_space_size: u8,
+    granule_size: u64,
+    top_bit: u8,
+    phys_ranges: &PyList,
+    hierarchical_permissions: bool,
+    mair: Option<u64>,
+) -> PyResult<String> {
+    let mut memory_view = create_memory_view(fd, &phys_ranges)?;
+    let mut arm_context = create_arm_context(
+        arm::ArmFlavour::Arm64,
+        granule_size_to_granule(granule_size),
+        address_space_size,
+        top_bit,
+    )?;
+    arm_context.set_hierarchical_permissions(hierarchical_permissions);
+    if let Some(mair) = mair {
+        arm_context.set_mair(mair);
+    }
+    let translation = arm::translate(&arm_context, &mut memory_view, pt_pa, va);
+    if let Ok(translation_ok) = translation {
+        Ok(translation_ok.to_string())
+    } else {
+        return Err(PyTypeError::new_err("Failed to translate address"));
+    }
+}
+
+fn create_arm_context(
+    flavour: arm::ArmFlavour,
+    granularity: arm::Granularity,
+    address_space_size: u8,
+    top_bit: u8,
+) -> PyResult<arm::ArmContext> {
+    match arm::ArmContext::new(flavour, granularity, address_space_size, top_bit) {
+        Ok(context) => Ok(context),
+        Err(_) => Err(PyValueError::new_err(format!(
+            "Invalid address space size: {}",
+            address_space_size
+        ))),
+    }
+}
+
 fn granule_size_to_granule(size: u64) -> arm::Granularity {
     match size {
         0x1000 => arm::Granularity::Pt4k,
@@ -178,7 +506,7 @@ fn granule_size_to_granule(size: u64) -> arm::Granularity {
     }
 }
 
-#[pyfunction]
+#[pyfunction(hierarchical_permissions = "true", mair = "None")]
 fn parse_page_table_aarch64(
     fd: i32,
     pt_pa: u64,
@@ -186,14 +514,83 @@ fn parse_page_table_aarch64(
     granule_size: u64,
     top_bit: u8,
     phys_ranges: &PyList,
+    hierarchical_permissions: bool,
+    mair: Option<u64>,
 ) -> PyResult<PageTableAarch64> {
     let mut memory_view = create_memory_view(fd, &phys_ranges)?;
-    let arm_context = arm::ArmContext::new(
+    let mut arm_context = create_arm_context(
         arm::ArmFlavour::Arm64,
         granule_size_to_granule(granule_size),
         address_space_size,
         top_bit,
-    );
+    )?;
+    arm_context.set_hierarchical_permissions(hierarchical_permissions);
+    if let Some(mair) = mair {
+        arm_context.set_mair(mair);
+    }
+    let pages = arm::collect_pages(&arm_context, &mut memory_view, pt_pa);
+    if let Ok(pages_ok) = pages {
+        Ok(PageTableAarch64::new(
+            pages_to_ranges(&pages_ok),
+            memory_view,
+        ))
+    } else {
+        return Err(PyTypeError::new_err("Failed to collect pages"));
+    }
+}
+
+// Walks the root table entries on multiple threads, each with its own clone of the memory view.
+#[pyfunction(hierarchical_permissions = "true", mair = "None")]
+fn parse_page_table_aarch64_parallel(
+    fd: i32,
+    pt_pa: u64,
+    address_space_size: u8,
+    granule_size: u64,
+    top_bit: u8,
+    phys_ranges: &PyList,
+    hierarchical_permissions: bool,
+    mair: Option<u64>,
+) -> PyResult<PageTableAarch64> {
+    let memory_view = create_memory_view(fd, &phys_ranges)?;
+    let mut arm_context = create_arm_context(
+        arm::ArmFlavour::Arm64,
+        granule_size_to_granule(granule_size),
+        address_space_size,
+        top_bit,
+    )?;
+    arm_context.set_hierarchical_permissions(hierarchical_permissions);
+    if let Some(mair) = mair {
+        arm_context.set_mair(mair);
+    }
+    let pages = arm::collect_pages_parallel(&arm_context, &memory_view, pt_pa);
+    if let Ok(pages_ok) = pages {
+        Ok(PageTableAarch64::new(pages_ok, memory_view))
+    } else {
+        return Err(PyTypeError::new_err("Failed to collect pages"));
+    }
+}
+
+#[pyfunction(dacr = "None")]
+fn parse_page_table_arm32(
+    fd: i32,
+    pt_pa: u64,
+    lpae: bool,
+    address_space_size: u8,
+    top_bit: u8,
+    phys_ranges: &PyList,
+    dacr: Option<u32>,
+) -> PyResult<PageTableAarch64> {
+    let mut memory_view = create_memory_view(fd, &phys_ranges)?;
+    let flavour = if lpae {
+        arm::ArmFlavour::Arm32Lpae
+    } else {
+        arm::ArmFlavour::Arm32
+    };
+    let mut arm_context =
+        create_arm_context(flavour, arm::Granularity::Pt4k, address_space_size, top_bit)?;
+    if let Some(dacr) = dacr {
+        arm_context.set_dacr(dacr);
+    }
     let pages = arm::collect_pages(&arm_context, &mut memory_view, pt_pa);
     if let Ok(pages_ok) = pages {
         Ok(PageTableAarch64::new(
@@ -206,6 +603,24 @@ fn parse_page_table_aarch64(
 }
 
 #[pyfunction]
+fn parse_page_table_riscv64(
+    fd: i32,
+    satp: u64,
+    phys_ranges: &PyList,
+) -> PyResult<PageTableRiscv64> {
+    let mut memory_view = create_memory_view(fd, &phys_ranges)?;
+    let pages = riscv::collect_pages_from_satp(&mut memory_view, satp);
+    if let Ok(pages_ok) = pages {
+        Ok(PageTableRiscv64::new(
+            pages_to_ranges(&pages_ok),
+            memory_view,
+        ))
+    } else {
+        return Err(PyTypeError::new_err("Failed to collect pages"));
+    }
+}
+
+#[pyfunction(hierarchical_permissions = "true", mair = "None")]
 fn parse_page_tabls_user_and_kernel_aarch64(
     fd: i32,
     ttbr0_pa: u64,
@@ -215,24 +630,34 @@ fn parse_page_tabls_user_and_kernel_aarch64(
     t0_granule_size: u64,
     t1_granule_size: u64,
     phys_ranges: &PyList,
+    hierarchical_permissions: bool,
+    mair: Option<u64>,
 ) -> PyResult<PageTableAarch64> {
     let mut memory_view = create_memory_view(fd, &phys_ranges)?;
     let pages_0 = {
-        let arm_context = arm::ArmContext::new(
+        let mut arm_context = create_arm_context(
             arm::ArmFlavour::Arm64,
             granule_size_to_granule(t0_granule_size),
             address_space_t0_size,
             0,
-        );
+        )?;
+        arm_context.set_hierarchical_permissions(hierarchical_permissions);
+        if let Some(mair) = mair {
+            arm_context.set_mair(mair);
+        }
         arm::collect_pages(&arm_context, &mut memory_view, ttbr0_pa)
     };
     let pages_1 = {
-        let arm_context = arm::ArmContext::new(
+        let mut arm_context = create_arm_context(
             arm::ArmFlavour::Arm64,
             granule_size_to_granule(t1_granule_size),
             address_space_t1_size,
             1,
-        );
+        )?;
+        arm_context.set_hierarchical_permissions(hierarchical_permissions);
+        if let Some(mair) = mair {
+            arm_context.set_mair(mair);
+        }
         arm::collect_pages(&arm_context, &mut memory_view, ttbr1_pa)
     };
     let mut all_pages = Vec::new();
@@ -274,6 +699,9 @@ fn filter_page_table_x86(table: &mut PageTableX86, filter: &PyAny) -> PyResult<P
     if let Some(r) = filter.va_range {
         pt_filter.set_va_range(r.0, r.1);
     }
+    if let Some(g) = filter.global {
+        pt_filter.set_global(g);
+    }
 
     let memory_view = table.get_memory_view().clone();
     Ok(PageTableX86::new(
@@ -304,6 +732,12 @@ fn filter_page_table_aarch64(table: &mut PageTableAarch64, filter: &PyAny) -> Py
     if let Some(r) = filter.va_range {
         pt_filter.set_va_range(r.0, r.1);
     }
+    if let Some(g) = filter.global {
+        pt_filter.set_global(g);
+    }
+    if let Some(d) = filter.device_memory {
+        pt_filter.set_device_memory(d);
+    }
 
     let memory_view = table.get_memory_view().clone();
     Ok(PageTableAarch64::new(
@@ -312,41 +746,114 @@ fn filter_page_table_aarch64(table: &mut PageTableAarch64, filter: &PyAny) -> Py
     ))
 }
 
-fn search_memory_generic<
-    PageRangeType: GenericPageRange + ToString,
-    PageTableType: PageTable<PageRangeType>,
->(
-    table: &mut PageTableType,
-    data: &[u8],
-    alignment: u64,
-    max_found: usize,
-) -> PyResult<String> {
-    let mut memory_view = table.get_memory_view().clone();
-    let ranges = table.get_ranges();
-    let search_result = bytes_search::search_memory_generic(
-        &data,
-        ranges,
-        &mut memory_view,
-        Some(alignment),
-        max_found,
-    );
-    let occs = search_result.get_results();
-    if occs.is_empty() {
-        return Ok(String::from("Not found"));
+#[pyfunction]
+fn filter_page_table_riscv64(
+    table: &mut PageTableRiscv64,
+    filter: &PyAny,
+) -> PyResult<PageTableRiscv64> {
+    let filter: PyFilterCommon = filter.extract()?;
+    let mut pt_filter = PageRangeFilterX86::new();
+    if let Some(e) = filter.executable {
+        pt_filter.set_executable(e);
     }
-
-    let mut saved_range = (occs[0].range_index, ranges[occs[0].range_index].to_string());
-    let mut result_str = String::new();
-    for occ in occs {
-        if occ.range_index != saved_range.0 {
-            saved_range = (occ.range_index, ranges[occ.range_index].to_string())
+    if let Some(w) = filter.writeable {
+        pt_filter.set_writeable(w);
+    }
+    if let Some(has_address) = filter.has_address {
+        pt_filter.set_has_address(has_address);
+    }
+    if let Some(su) = filter.only_superuser_accessible {
+        pt_filter.set_superuser_accessible(su);
+    }
+    if let Some(u) = filter.user_accessible {
+        pt_filter.set_user_accessible(u);
+    }
+    if let Some(r) = filter.va_range {
+        pt_filter.set_va_range(r.0, r.1);
+    }
+    if let Some(g) = filter.global {
+        pt_filter.set_global(g);
+    }
+
+    let memory_view = table.get_memory_view().clone();
+    Ok(PageTableRiscv64::new(
+        page_range_filter::filter_riscv_ranges(table.get_ranges(), &pt_filter),
+        memory_view,
+    ))
+}
+
+fn search_result_to_string<PageRangeType: ToString>(
+    ranges: &Vec<PageRangeType>,
+    search_result: &bytes_search::SearchResult,
+) -> String {
+    let occs = search_result.get_results();
+    if occs.is_empty() {
+        return String::from("Not found");
+    }
+
+    let mut saved_range = (occs[0].range_index, ranges[occs[0].range_index].to_string());
+    let mut result_str = String::new();
+    for occ in occs {
+        if occ.range_index != saved_range.0 {
+            saved_range = (occ.range_index, ranges[occ.range_index].to_string())
         }
         result_str.push_str(&format!(
             "Found at 0x{:016x} in {}\n",
             occ.va, saved_range.1
         ));
     }
-    Ok(result_str)
+    result_str
+}
+
+fn search_memory_generic<
+    PageRangeType: GenericPageRange + ToString + Sync,
+    PageTableType: PageTable<PageRangeType>,
+>(
+    table: &mut PageTableType,
+    data: &[u8],
+    alignment: u64,
+    max_found: usize,
+) -> PyResult<String> {
+    let memory_view = table.get_memory_view().clone();
+    let ranges = table.get_ranges();
+    let search_result = bytes_search::search_memory_parallel(
+        &data,
+        ranges,
+        &memory_view,
+        Some(alignment),
+        max_found,
+    );
+    Ok(search_result_to_string(ranges, &search_result))
+}
+
+fn search_memory_pattern_generic<
+    PageRangeType: GenericPageRange + ToString + Sync,
+    PageTableType: PageTable<PageRangeType>,
+>(
+    table: &mut PageTableType,
+    pattern: &str,
+    alignment: u64,
+    max_found: usize,
+) -> PyResult<String> {
+    let pattern = match bytes_search::BytePattern::parse(pattern) {
+        Ok(pattern) => pattern,
+        Err(err) => {
+            return Err(PyValueError::new_err(format!(
+                "Invalid byte pattern: {:?}",
+                err
+            )))
+        }
+    };
+    let memory_view = table.get_memory_view().clone();
+    let ranges = table.get_ranges();
+    let search_result = bytes_search::search_memory_pattern_parallel(
+        &pattern,
+        ranges,
+        &memory_view,
+        Some(alignment),
+        max_found,
+    );
+    Ok(search_result_to_string(ranges, &search_result))
 }
 
 #[pyfunction]
@@ -369,6 +876,555 @@ fn search_memory_aarch64(
     search_memory_generic(table, data_py.as_bytes(), alignment, max_found)
 }
 
+#[pyfunction]
+fn search_memory_riscv64(
+    table: &mut PageTableRiscv64,
+    data_py: &PyBytes,
+    alignment: u64,
+    max_found: usize,
+) -> PyResult<String> {
+    search_memory_generic(table, data_py.as_bytes(), alignment, max_found)
+}
+
+#[pyfunction]
+fn search_memory_ept(
+    table: &mut PageTableEpt,
+    data_py: &PyBytes,
+    alignment: u64,
+    max_found: usize,
+) -> PyResult<String> {
+    search_memory_generic(table, data_py.as_bytes(), alignment, max_found)
+}
+
+// Returns (va, length, range) for every match.
+fn search_memory_regex_generic<
+    PageRangeType: GenericPageRange + ToString + Sync,
+    PageTableType: PageTable<PageRangeType>,
+>(
+    table: &mut PageTableType,
+    pattern: &str,
+    alignment: u64,
+    max_found: usize,
+    max_match_len: usize,
+) -> PyResult<Vec<(u64, u64, String)>> {
+    let pattern = match bytes_search::RegexPattern::new(pattern, max_match_len) {
+        Ok(pattern) => pattern,
+        Err(err) => {
+            return Err(PyValueError::new_err(format!(
+                "Invalid regular expression or max_match_len: {:?}",
+                err
+            )))
+        }
+    };
+    let memory_view = table.get_memory_view().clone();
+    let ranges = table.get_ranges();
+    let search_result = bytes_search::search_memory_pattern_parallel(
+        &pattern,
+        ranges,
+        &memory_view,
+        Some(alignment),
+        max_found,
+    );
+    Ok(search_result
+        .get_results()
+        .iter()
+        .map(|occ| (occ.va, occ.length, ranges[occ.range_index].to_string()))
+        .collect())
+}
+
+// Returns (va, needle index, range) for every match.
+fn search_memory_multi_generic<
+    PageRangeType: GenericPageRange + ToString + Sync,
+    PageTableType: PageTable<PageRangeType>,
+>(
+    table: &mut PageTableType,
+    needles: Vec<&[u8]>,
+    alignment: u64,
+    max_found: usize,
+) -> PyResult<Vec<(u64, usize, String)>> {
+    let pattern = match bytes_search::MultiNeedlePattern::new(&needles) {
+        Ok(pattern) => pattern,
+        Err(err) => return Err(PyValueError::new_err(format!("Invalid needles: {:?}", err))),
+    };
+    let memory_view = table.get_memory_view().clone();
+    let ranges = table.get_ranges();
+    let search_result = bytes_search::search_memory_pattern_parallel(
+        &pattern,
+        ranges,
+        &memory_view,
+        Some(alignment),
+        max_found,
+    );
+    Ok(search_result
+        .get_results()
+        .iter()
+        .map(|occ| {
+            (
+                occ.va,
+                occ.needle_index,
+                ranges[occ.range_index].to_string(),
+            )
+        })
+        .collect())
+}
+
+// Returns (va, pointer value, range) for every match.
+fn search_memory_pointers_generic<
+    PageRangeType: GenericPageRange + ToString + Sync,
+    PageTableType: PageTable<PageRangeType>,
+>(
+    table: &mut PageTableType,
+    va_start: u64,
+    va_end: u64,
+    alignment: u64,
+    max_found: usize,
+    pointer_size: usize,
+    mapped_only: bool,
+) -> PyResult<Vec<(u64, u64, String)>> {
+    let mut pattern = match bytes_search::PointerPattern::new(pointer_size, va_start, va_end) {
+        Ok(pattern) => pattern,
+        Err(err) => {
+            return Err(PyValueError::new_err(format!(
+                "Invalid pointer search: {:?}",
+                err
+            )))
+        }
+    };
+    if mapped_only {
+        pattern = pattern.restrict_to_mapped(table.get_ranges());
+    }
+    let mut memory_view = table.get_memory_view().clone();
+    let ranges = table.get_ranges();
+    let search_result = bytes_search::search_memory_pattern_parallel(
+        &pattern,
+        ranges,
+        &memory_view,
+        Some(alignment),
+        max_found,
+    );
+    let mut virtual_memory = VirtualMemoryView::new(&mut memory_view, ranges);
+    let mut result = vec![];
+    for occ in search_result.get_results() {
+        let value = match virtual_memory.read_block(occ.va as usize, pointer_size) {
+            Ok(block) => block
+                .iter()
+                .rev()
+                .fold(0u64, |value, byte| (value << 8) | *byte as u64),
+            Err(_) => return Err(PyTypeError::new_err("Failed to read pointer")),
+        };
+        result.push((occ.va, value, ranges[occ.range_index].to_string()));
+    }
+    Ok(result)
+}
+
+#[pyfunction]
+fn search_memory_pattern_x86(
+    table: &mut PageTableX86,
+    pattern: &str,
+    alignment: u64,
+    max_found: usize,
+) -> PyResult<String> {
+    search_memory_pattern_generic(table, pattern, alignment, max_found)
+}
+
+#[pyfunction]
+fn search_memory_pattern_aarch64(
+    table: &mut PageTableAarch64,
+    pattern: &str,
+    alignment: u64,
+    max_found: usize,
+) -> PyResult<String> {
+    search_memory_pattern_generic(table, pattern, alignment, max_found)
+}
+
+#[pyfunction]
+fn search_memory_pattern_riscv64(
+    table: &mut PageTableRiscv64,
+    pattern: &str,
+    alignment: u64,
+    max_found: usize,
+) -> PyResult<String> {
+    search_memory_pattern_generic(table, pattern, alignment, max_found)
+}
+
+#[pyfunction]
+fn search_memory_pattern_ept(
+    table: &mut PageTableEpt,
+    pattern: &str,
+    alignment: u64,
+    max_found: usize,
+) -> PyResult<String> {
+    search_memory_pattern_generic(table, pattern, alignment, max_found)
+}
+
+#[pyfunction(max_match_len = "4096")]
+fn search_memory_regex_x86(
+    table: &mut PageTableX86,
+    pattern: &str,
+    alignment: u64,
+    max_found: usize,
+    max_match_len: usize,
+) -> PyResult<Vec<(u64, u64, String)>> {
+    search_memory_regex_generic(table, pattern, alignment, max_found, max_match_len)
+}
+
+#[pyfunction(max_match_len = "4096")]
+fn search_memory_regex_aarch64(
+    table: &mut PageTableAarch64,
+    pattern: &str,
+    alignment: u64,
+    max_found: usize,
+    max_match_len: usize,
+) -> PyResult<Vec<(u64, u64, String)>> {
+    search_memory_regex_generic(table, pattern, alignment, max_found, max_match_len)
+}
+
+#[pyfunction(max_match_len = "4096")]
+fn search_memory_regex_riscv64(
+    table: &mut PageTableRiscv64,
+    pattern: &str,
+    alignment: u64,
+    max_found: usize,
+    max_match_len: usize,
+) -> PyResult<Vec<(u64, u64, String)>> {
+    search_memory_regex_generic(table, pattern, alignment, max_found, max_match_len)
+}
+
+#[pyfunction(max_match_len = "4096")]
+fn search_memory_regex_ept(
+    table: &mut PageTableEpt,
+    pattern: &str,
+    alignment: u64,
+    max_found: usize,
+    max_match_len: usize,
+) -> PyResult<Vec<(u64, u64, String)>> {
+    search_memory_regex_generic(table, pattern, alignment, max_found, max_match_len)
+}
+
+#[pyfunction]
+fn search_memory_multi_x86(
+    table: &mut PageTableX86,
+    needles: Vec<&[u8]>,
+    alignment: u64,
+    max_found: usize,
+) -> PyResult<Vec<(u64, usize, String)>> {
+    search_memory_multi_generic(table, needles, alignment, max_found)
+}
+
+#[pyfunction]
+fn search_memory_multi_aarch64(
+    table: &mut PageTableAarch64,
+    needles: Vec<&[u8]>,
+    alignment: u64,
+    max_found: usize,
+) -> PyResult<Vec<(u64, usize, String)>> {
+    search_memory_multi_generic(table, needles, alignment, max_found)
+}
+
+#[pyfunction]
+fn search_memory_multi_riscv64(
+    table: &mut PageTableRiscv64,
+    needles: Vec<&[u8]>,
+    alignment: u64,
+    max_found: usize,
+) -> PyResult<Vec<(u64, usize, String)>> {
+    search_memory_multi_generic(table, needles, alignment, max_found)
+}
+
+#[pyfunction]
+fn search_memory_multi_ept(
+    table: &mut PageTableEpt,
+    needles: Vec<&[u8]>,
+    alignment: u64,
+    max_found: usize,
+) -> PyResult<Vec<(u64, usize, String)>> {
+    search_memory_multi_generic(table, needles, alignment, max_found)
+}
+
+#[pyfunction(pointer_size = "8", mapped_only = "false")]
+fn search_memory_pointers_x86(
+    table: &mut PageTableX86,
+    va_start: u64,
+    va_end: u64,
+    alignment: u64,
+    max_found: usize,
+    pointer_size: usize,
+    mapped_only: bool,
+) -> PyResult<Vec<(u64, u64, String)>> {
+    search_memory_pointers_generic(
+        table,
+        va_start,
+        va_end,
+        alignment,
+        max_found,
+        pointer_size,
+        mapped_only,
+    )
+}
+
+#[pyfunction(pointer_size = "8", mapped_only = "false")]
+fn search_memory_pointers_aarch64(
+    table: &mut PageTableAarch64,
+    va_start: u64,
+    va_end: u64,
+    alignment: u64,
+    max_found: usize,
+    pointer_size: usize,
+    mapped_only: bool,
+) -> PyResult<Vec<(u64, u64, String)>> {
+    search_memory_pointers_generic(
+        table,
+        va_start,
+        va_end,
+        alignment,
+        max_found,
+        pointer_size,
+        mapped_only,
+    )
+}
+
+#[pyfunction(pointer_size = "8", mapped_only = "false")]
+fn search_memory_pointers_riscv64(
+    table: &mut PageTableRiscv64,
+    va_start: u64,
+    va_end: u64,
+    alignment: u64,
+    max_found: usize,
+    pointer_size: usize,
+    mapped_only: bool,
+) -> PyResult<Vec<(u64, u64, String)>> {
+    search_memory_pointers_generic(
+        table,
+        va_start,
+        va_end,
+        alignment,
+        max_found,
+        pointer_size,
+        mapped_only,
+    )
+}
+
+#[pyfunction(pointer_size = "8", mapped_only = "false")]
+fn search_memory_pointers_ept(
+    table: &mut PageTableEpt,
+    va_start: u64,
+    va_end: u64,
+    alignment: u64,
+    max_found: usize,
+    pointer_size: usize,
+    mapped_only: bool,
+) -> PyResult<Vec<(u64, u64, String)>> {
+    search_memory_pointers_generic(
+        table,
+        va_start,
+        va_end,
+        alignment,
+        max_found,
+        pointer_size,
+        mapped_only,
+    )
+}
+
+// Returns (pa, score) of the best candidates.
+fn find_page_table_roots_generic(
+    fd: i32,
+    phys_ranges: &PyList,
+    flavour: root_scan::RootScanFlavour,
+    max_candidates: usize,
+) -> PyResult<Vec<(u64, u64)>> {
+    let ram_ranges: Vec<common::PhysRange> = collect_ram_ranges(phys_ranges)?
+        .iter()
+        .map(|ram_range| ram_range.get_phys_range())
+        .collect();
+    let mut memory_view = create_memory_view(fd, phys_ranges)?;
+    let candidates = root_scan::scan_for_roots(&mut memory_view, &ram_ranges, flavour);
+    Ok(candidates
+        .iter()
+        .take(max_candidates)
+        .map(|candidate| (candidate.pa, candidate.score))
+        .collect())
+}
+
+// Returns (plausible, tables visited, leaf entries, invalid entries).
+fn validate_page_table_root_generic(
+    fd: i32,
+    phys_ranges: &PyList,
+    flavour: root_scan::RootScanFlavour,
+    pa: u64,
+    max_tables: usize,
+) -> PyResult<(bool, usize, usize, usize)> {
+    let ram_ranges: Vec<common::PhysRange> = collect_ram_ranges(phys_ranges)?
+        .iter()
+        .map(|ram_range| ram_range.get_phys_range())
+        .collect();
+    let mut memory_view = create_memory_view(fd, phys_ranges)?;
+    let validation =
+        root_scan::validate_root(&mut memory_view, &ram_ranges, flavour, pa, max_tables);
+    match validation {
+        Ok(validation) => Ok((
+            validation.is_plausible(),
+            validation.tables_visited,
+            validation.leaf_entries,
+            validation.invalid_entries,
+        )),
+        Err(_) => Err(PyTypeError::new_err("Failed to read the root table")),
+    }
+}
+
+#[pyfunction(max_candidates = "16")]
+fn find_page_table_roots_x86_64(
+    fd: i32,
+    phys_ranges: &PyList,
+    max_candidates: usize,
+) -> PyResult<Vec<(u64, u64)>> {
+    find_page_table_roots_generic(
+        fd,
+        phys_ranges,
+        root_scan::RootScanFlavour::X86_64,
+        max_candidates,
+    )
+}
+
+#[pyfunction(max_candidates = "16")]
+fn find_page_table_roots_aarch64(
+    fd: i32,
+    phys_ranges: &PyList,
+    max_candidates: usize,
+) -> PyResult<Vec<(u64, u64)>> {
+    find_page_table_roots_generic(
+        fd,
+        phys_ranges,
+        root_scan::RootScanFlavour::Aarch64,
+        max_candidates,
+    )
+}
+
+#[pyfunction(max_tables = "4096")]
+fn validate_page_table_root_x86_64(
+    fd: i32,
+    phys_ranges: &PyList,
+    pa: u64,
+    max_tables: usize,
+) -> PyResult<(bool, usize, usize, usize)> {
+    validate_page_table_root_generic(
+        fd,
+        phys_ranges,
+        root_scan::RootScanFlavour::X86_64,
+        pa,
+        max_tables,
+    )
+}
+
+#[pyfunction(max_tables = "4096")]
+fn validate_page_table_root_aarch64(
+    fd: i32,
+    phys_ranges: &PyList,
+    pa: u64,
+    max_tables: usize,
+) -> PyResult<(bool, usize, usize, usize)> {
+    validate_page_table_root_generic(
+        fd,
+        phys_ranges,
+        root_scan::RootScanFlavour::Aarch64,
+        pa,
+        max_tables,
+    )
+}
+
+// Returns one list per kernel with (root pa, user ranges, mapped bytes, writeable bytes,
+// executable bytes) for each of its processes.
+#[pyfunction]
+fn enumerate_processes_x86_64(
+    fd: i32,
+    phys_ranges: &PyList,
+) -> PyResult<Vec<Vec<(u64, usize, u64, u64, u64)>>> {
+    let ram_ranges: Vec<common::PhysRange> = collect_ram_ranges(phys_ranges)?
+        .iter()
+        .map(|ram_range| ram_range.get_phys_range())
+        .collect();
+    let mut memory_view = create_memory_view(fd, phys_ranges)?;
+    let candidates = root_scan::scan_for_roots(
+        &mut memory_view,
+        &ram_ranges,
+        root_scan::RootScanFlavour::X86_64,
+    );
+    let kernels = address_spaces::group_into_address_spaces(&mut memory_view, &candidates);
+    Ok(kernels
+        .iter()
+        .map(|kernel| {
+            kernel
+                .processes
+                .iter()
+                .map(|process| {
+                    let user_space = &process.user_space;
+                    (
+                        process.root_pa,
+                        user_space.ranges,
+                        user_space.mapped_bytes,
+                        user_space.writeable_bytes,
+                        user_space.executable_bytes,
+                    )
+                })
+                .collect()
+        })
+        .collect())
+}
+
+fn find_virtual_addresses_generic<
+    PageRangeType: GenericPageRange + ToString,
+    PageTableType: PageTable<PageRangeType>,
+>(
+    table: &PageTableType,
+    pa: u64,
+    extent: u64,
+) -> PyResult<String> {
+    let ranges = table.get_ranges();
+    let mappings = reverse_mapping::find_virtual_addresses(ranges, pa, extent);
+    if mappings.is_empty() {
+        return Ok(String::from("Not found"));
+    }
+
+    let mut result_str = String::new();
+    for mapping in mappings {
+        result_str.push_str(&format!(
+            "0x{:016x} maps 0x{:x} (length 0x{:x}) in {}\n",
+            mapping.va,
+            mapping.pa,
+            mapping.extent,
+            ranges[mapping.range_index].to_string()
+        ));
+    }
+    Ok(result_str)
+}
+
+#[pyfunction(extent = "1")]
+fn find_virtual_addresses_x86(table: &PageTableX86, pa: u64, extent: u64) -> PyResult<String> {
+    find_virtual_addresses_generic(table, pa, extent)
+}
+
+#[pyfunction(extent = "1")]
+fn find_virtual_addresses_aarch64(
+    table: &PageTableAarch64,
+    pa: u64,
+    extent: u64,
+) -> PyResult<String> {
+    find_virtual_addresses_generic(table, pa, extent)
+}
+
+#[pyfunction(extent = "1")]
+fn find_virtual_addresses_riscv64(
+    table: &PageTableRiscv64,
+    pa: u64,
+    extent: u64,
+) -> PyResult<String> {
+    find_virtual_addresses_generic(table, pa, extent)
+}
+
+#[pyfunction(extent = "1")]
+fn find_virtual_addresses_ept(table: &PageTableEpt, pa: u64, extent: u64) -> PyResult<String> {
+    find_virtual_addresses_generic(table, pa, extent)
+}
+
 #[pyclass]
 struct KaslrInfo {
     #[pyo3(get)]
@@ -381,70 +1437,62 @@ struct KaslrInfo {
     physmap_virt: Option<u64>,
 }
 
+impl From<kaslr::KaslrInfo> for KaslrInfo {
+    fn from(kaslr_info: kaslr::KaslrInfo) -> Self {
+        Self {
+            image_virt: kaslr_info.image_virt,
+            image_phys: kaslr_info.image_phys,
+            physmap_virt: kaslr_info.physmap_virt,
+        }
+    }
+}
+
 #[pyfunction]
 fn find_kaslr_linux_x86(table: &mut PageTableX86) -> PyResult<KaslrInfo> {
-    let mut kaslr_info = KaslrInfo {
-        image_virt: None,
-        image_phys: None,
-        physmap_virt: None,
-    };
-
-    let mut filter_and_search = |is_executable: bool| -> Option<(u64, Option<u64>)> {
-        let memory_view = table.get_memory_view().clone();
-        let mut pt_filter = PageRangeFilterX86::new();
-        pt_filter.set_executable(is_executable);
-        pt_filter.set_superuser_accessible(true);
-        let filtered_pt = PageTableX86::new(
-            page_range_filter::filter_x86_ranges(table.get_ranges(), &pt_filter),
-            memory_view,
-        );
-
-        // Next find 2-MiB-aligned pages which has the byte 0x48 at the beginning.
-        let alignment = 2 * 1024 * 1024;
-        let linux_base_needle = [0x48_u8];
-
-        let mut memory_view = table.get_memory_view().clone();
-        let ranges = filtered_pt.get_ranges();
-        let search_result = bytes_search::search_memory_generic(
-            &linux_base_needle,
-            ranges,
-            &mut memory_view,
-            Some(alignment),
-            1,
-        );
-        let occ = search_result.get_results();
-        if occ.is_empty() {
-            None
-        } else {
-            Some((
-                occ[0].va,
-                filtered_pt.get_ranges()[occ[0].range_index].gva_to_gpa(occ[0].va),
-            ))
-        }
-    };
+    let mut memory_view = table.get_memory_view().clone();
+    Ok(kaslr::find_kaslr_linux_x86_64(table.get_ranges(), &mut memory_view).into())
+}
 
-    let image_info = filter_and_search(true);
-    if let Some(image_info_ok) = image_info {
-        kaslr_info.image_virt = Some(image_info_ok.0);
-        kaslr_info.image_phys = image_info_ok.1;
-    }
+#[pyfunction]
+fn find_kaslr_linux_aarch64(table: &mut PageTableAarch64) -> PyResult<KaslrInfo> {
+    let mut memory_view = table.get_memory_view().clone();
+    Ok(kaslr::find_kaslr_linux_aarch64(table.get_ranges(), &mut memory_view).into())
+}
 
-    let physbase_info = filter_and_search(false);
-    if let Some(physbase_info_ok) = physbase_info {
-        kaslr_info.physmap_virt = Some(physbase_info_ok.0);
-    }
+#[pyfunction]
+fn find_kaslr_linux_riscv64(table: &mut PageTableRiscv64) -> PyResult<KaslrInfo> {
+    let mut memory_view = table.get_memory_view().clone();
+    Ok(kaslr::find_kaslr_linux_riscv64(table.get_ranges(), &mut memory_view).into())
+}
 
-    Ok(kaslr_info)
+#[pyfunction]
+fn find_kaslr_linux_x86_32(table: &mut PageTableX86) -> PyResult<KaslrInfo> {
+    let mut memory_view = table.get_memory_view().clone();
+    Ok(kaslr::find_kaslr_linux_x86_32(table.get_ranges(), &mut memory_view).into())
 }
 
 #[pymodule]
 fn pt_dump_py(_py: Python, m: &PyModule) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(parse_page_table_x86_32, m)?)?;
     m.add_function(wrap_pyfunction!(parse_page_table_x86_64, m)?)?;
+    m.add_function(wrap_pyfunction!(parse_page_table_x86_64_parallel, m)?)?;
+    m.add_function(wrap_pyfunction!(dump_page_table_x86_64, m)?)?;
+    m.add_function(wrap_pyfunction!(search_memory_walk_x86_64, m)?)?;
     m.add_function(wrap_pyfunction!(parse_page_table_aarch64, m)?)?;
-    // TODO: riscv64
+    m.add_function(wrap_pyfunction!(parse_page_table_aarch64_parallel, m)?)?;
+    m.add_function(wrap_pyfunction!(parse_page_table_arm32, m)?)?;
+    m.add_function(wrap_pyfunction!(parse_page_table_riscv64, m)?)?;
+    m.add_function(wrap_pyfunction!(parse_page_table_npt, m)?)?;
+    m.add_function(wrap_pyfunction!(parse_page_table_ept, m)?)?;
+    m.add_function(wrap_pyfunction!(parse_page_table_aarch64_stage2, m)?)?;
+    m.add_function(wrap_pyfunction!(translate_x86_64, m)?)?;
+    m.add_function(wrap_pyfunction!(translate_aarch64, m)?)?;
     m.add_function(wrap_pyfunction!(get_page_table_as_string_x86, m)?)?;
     m.add_function(wrap_pyfunction!(get_page_table_as_string_aarch64, m)?)?;
+    m.add_function(wrap_pyfunction!(get_page_table_as_string_riscv64, m)?)?;
+    m.add_function(wrap_pyfunction!(get_page_table_as_string_ept, m)?)?;
+    m.add_function(wrap_pyfunction!(diff_page_tables_x86, m)?)?;
+    m.add_function(wrap_pyfunction!(diff_page_tables_aarch64, m)?)?;
     m.add_function(wrap_pyfunction!(
         parse_page_tabls_user_and_kernel_aarch64,
         m
@@ -452,15 +1500,42 @@ fn pt_dump_py(_py: Python, m: &PyModule) -> PyResult<()> {
 
     m.add_function(wrap_pyfunction!(filter_page_table_x86, m)?)?;
     m.add_function(wrap_pyfunction!(filter_page_table_aarch64, m)?)?;
-    // TODO: riscv64
+    m.add_function(wrap_pyfunction!(filter_page_table_riscv64, m)?)?;
 
     m.add_function(wrap_pyfunction!(search_memory_x86, m)?)?;
     m.add_function(wrap_pyfunction!(search_memory_aarch64, m)?)?;
-    // TODO: riscv64
+    m.add_function(wrap_pyfunction!(search_memory_riscv64, m)?)?;
+    m.add_function(wrap_pyfunction!(search_memory_ept, m)?)?;
+    m.add_function(wrap_pyfunction!(search_memory_pattern_x86, m)?)?;
+    m.add_function(wrap_pyfunction!(search_memory_pattern_aarch64, m)?)?;
+    m.add_function(wrap_pyfunction!(search_memory_pattern_riscv64, m)?)?;
+    m.add_function(wrap_pyfunction!(search_memory_pattern_ept, m)?)?;
+    m.add_function(wrap_pyfunction!(search_memory_regex_x86, m)?)?;
+    m.add_function(wrap_pyfunction!(search_memory_regex_aarch64, m)?)?;
+    m.add_function(wrap_pyfunction!(search_memory_regex_riscv64, m)?)?;
+    m.add_function(wrap_pyfunction!(search_memory_regex_ept, m)?)?;
+    m.add_function(wrap_pyfunction!(search_memory_multi_x86, m)?)?;
+    m.add_function(wrap_pyfunction!(search_memory_multi_aarch64, m)?)?;
+    m.add_function(wrap_pyfunction!(search_memory_multi_riscv64, m)?)?;
+    m.add_function(wrap_pyfunction!(search_memory_multi_ept, m)?)?;
+    m.add_function(wrap_pyfunction!(search_memory_pointers_x86, m)?)?;
+    m.add_function(wrap_pyfunction!(search_memory_pointers_aarch64, m)?)?;
+    m.add_function(wrap_pyfunction!(search_memory_pointers_riscv64, m)?)?;
+    m.add_function(wrap_pyfunction!(search_memory_pointers_ept, m)?)?;
+    m.add_function(wrap_pyfunction!(find_page_table_roots_x86_64, m)?)?;
+    m.add_function(wrap_pyfunction!(find_page_table_roots_aarch64, m)?)?;
+    m.add_function(wrap_pyfunction!(validate_page_table_root_x86_64, m)?)?;
+    m.add_function(wrap_pyfunction!(validate_page_table_root_aarch64, m)?)?;
+    m.add_function(wrap_pyfunction!(enumerate_processes_x86_64, m)?)?;
+    m.add_function(wrap_pyfunction!(find_virtual_addresses_x86, m)?)?;
+    m.add_function(wrap_pyfunction!(find_virtual_addresses_aarch64, m)?)?;
+    m.add_function(wrap_pyfunction!(find_virtual_addresses_riscv64, m)?)?;
+    m.add_function(wrap_pyfunction!(find_virtual_addresses_ept, m)?)?;
 
     m.add_function(wrap_pyfunction!(find_kaslr_linux_x86, m)?)?;
-    // TODO: aarch64
-    // TODO: riscv64
+    m.add_function(wrap_pyfunction!(find_kaslr_linux_aarch64, m)?)?;
+    m.add_function(wrap_pyfunction!(find_kaslr_linux_riscv64, m)?)?;
+    m.add_function(wrap_pyfunction!(find_kaslr_linux_x86_32, m)?)?;
 
     Ok(())
 }
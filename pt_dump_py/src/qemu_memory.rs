@@ -1,6 +1,10 @@
 use lru::LruCache;
 use nc;
-use pt_dump_lib::{memory::memory::MemoryView, memory::memory_fd::*, pt::common::Error};
+use pt_dump_lib::{
+    memory::memory::MemoryView,
+    memory::memory_fd::*,
+    pt::common::{Error, PhysRange},
+};
 use std::num::NonZeroUsize;
 
 #[derive(Copy, Clone, Debug)]
@@ -18,6 +22,10 @@ impl RamRange {
             hva: hva,
         }
     }
+
+    pub fn get_phys_range(&self) -> PhysRange {
+        PhysRange::new(self.gpa_start as u64, self.gpa_extent as u64)
+    }
 }
 
 pub struct QemuMemoryView {